@@ -0,0 +1,79 @@
+-- Duolingo
+CREATE TABLE IF NOT EXISTS duo_user_snapshots (
+    id BIGSERIAL PRIMARY KEY,
+    username TEXT NOT NULL,
+    duo_id BIGINT NOT NULL,
+    fullname TEXT,
+    learning_language TEXT NOT NULL,
+    fetched_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS duo_user_snapshots_username_idx
+    ON duo_user_snapshots (username, fetched_at DESC);
+
+CREATE TABLE IF NOT EXISTS duo_language_snapshots (
+    id BIGSERIAL PRIMARY KEY,
+    snapshot_id BIGINT NOT NULL REFERENCES duo_user_snapshots (id) ON DELETE CASCADE,
+    username TEXT NOT NULL,
+    language TEXT NOT NULL,
+    language_string TEXT NOT NULL,
+    learning BOOLEAN NOT NULL,
+    current_learning BOOLEAN NOT NULL,
+    points BIGINT NOT NULL,
+    level INTEGER NOT NULL,
+    streak INTEGER NOT NULL,
+    sentences_translated BIGINT NOT NULL,
+    to_next_level BIGINT NOT NULL,
+    fetched_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS duo_language_snapshots_username_idx
+    ON duo_language_snapshots (username, language, fetched_at DESC);
+
+-- GitHub
+CREATE TABLE IF NOT EXISTS github_runner_snapshots (
+    id BIGSERIAL PRIMARY KEY,
+    runner_id BIGINT NOT NULL,
+    name TEXT NOT NULL,
+    os TEXT NOT NULL,
+    status TEXT NOT NULL,
+    busy BOOLEAN NOT NULL,
+    labels TEXT[] NOT NULL DEFAULT '{}',
+    fetched_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS github_runner_snapshots_runner_idx
+    ON github_runner_snapshots (runner_id, fetched_at DESC);
+
+CREATE TABLE IF NOT EXISTS github_repo_snapshots (
+    id BIGSERIAL PRIMARY KEY,
+    repo_id BIGINT NOT NULL,
+    full_name TEXT NOT NULL,
+    language TEXT,
+    stargazers_count BIGINT NOT NULL,
+    forks_count BIGINT NOT NULL,
+    watchers_count BIGINT NOT NULL,
+    open_issues_count BIGINT NOT NULL,
+    size BIGINT NOT NULL,
+    archived BOOLEAN NOT NULL,
+    pushed_at TEXT NOT NULL,
+    fetched_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS github_repo_snapshots_repo_idx
+    ON github_repo_snapshots (repo_id, fetched_at DESC);
+
+-- Spotify
+CREATE TABLE IF NOT EXISTS spotify_plays (
+    id BIGSERIAL PRIMARY KEY,
+    played_at TIMESTAMPTZ NOT NULL UNIQUE,
+    track_id TEXT NOT NULL,
+    track_name TEXT NOT NULL,
+    artists JSONB NOT NULL,
+    album_id TEXT NOT NULL,
+    album_name TEXT NOT NULL,
+    context_uri TEXT,
+    context_type TEXT,
+    duration_ms BIGINT NOT NULL,
+    fetched_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,7 @@
+-- Artist metadata for genre analytics, filled in by the history ingester
+CREATE TABLE IF NOT EXISTS spotify_artists (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    genres TEXT[] NOT NULL DEFAULT '{}',
+    fetched_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
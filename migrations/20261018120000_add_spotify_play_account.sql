@@ -0,0 +1,8 @@
+-- Plays belong to a linked Spotify account; rows ingested before accounts existed
+-- keep an empty account until the legacy credentials are migrated
+ALTER TABLE spotify_plays ADD COLUMN IF NOT EXISTS account TEXT NOT NULL DEFAULT '';
+
+ALTER TABLE spotify_plays DROP CONSTRAINT IF EXISTS spotify_plays_played_at_key;
+-- Its (account, played_at) index also serves the per-account history and stats scans
+ALTER TABLE spotify_plays
+    ADD CONSTRAINT spotify_plays_account_played_at_key UNIQUE (account, played_at);
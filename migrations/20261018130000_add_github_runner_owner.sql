@@ -0,0 +1,5 @@
+-- Runners can now come from several organizations or users
+ALTER TABLE github_runner_snapshots ADD COLUMN IF NOT EXISTS owner TEXT NOT NULL DEFAULT '';
+
+CREATE INDEX IF NOT EXISTS github_runner_snapshots_owner_idx
+    ON github_runner_snapshots (owner, fetched_at DESC);
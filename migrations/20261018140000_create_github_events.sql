@@ -0,0 +1,15 @@
+-- GitHub webhook deliveries, kept for the activity feed
+CREATE TABLE IF NOT EXISTS github_events (
+    id BIGSERIAL PRIMARY KEY,
+    delivery_id TEXT NOT NULL UNIQUE,
+    event TEXT NOT NULL,
+    action TEXT,
+    repo TEXT,
+    sender TEXT,
+    summary TEXT NOT NULL,
+    payload JSONB NOT NULL,
+    received_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS github_events_received_idx ON github_events (received_at DESC);
+CREATE INDEX IF NOT EXISTS github_events_event_idx ON github_events (event, received_at DESC);
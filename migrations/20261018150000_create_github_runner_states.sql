@@ -0,0 +1,31 @@
+-- Last known state of each self-hosted runner
+CREATE TABLE IF NOT EXISTS github_runner_states (
+    owner TEXT NOT NULL,
+    runner_id BIGINT NOT NULL,
+    name TEXT NOT NULL,
+    os TEXT NOT NULL,
+    labels TEXT[] NOT NULL DEFAULT '{}',
+    status TEXT NOT NULL,
+    busy BOOLEAN NOT NULL,
+    status_since TIMESTAMPTZ NOT NULL,
+    last_seen_at TIMESTAMPTZ NOT NULL,
+    offline_alerted_at TIMESTAMPTZ,
+    PRIMARY KEY (owner, runner_id)
+);
+
+-- One row per stretch of time a runner spent in a status/busy combination;
+-- the open stretch has no ended_at
+CREATE TABLE IF NOT EXISTS github_runner_transitions (
+    id BIGSERIAL PRIMARY KEY,
+    owner TEXT NOT NULL,
+    runner_id BIGINT NOT NULL,
+    status TEXT NOT NULL,
+    busy BOOLEAN NOT NULL,
+    started_at TIMESTAMPTZ NOT NULL,
+    ended_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS github_runner_transitions_runner_idx
+    ON github_runner_transitions (owner, runner_id, started_at DESC);
+CREATE UNIQUE INDEX IF NOT EXISTS github_runner_transitions_open_idx
+    ON github_runner_transitions (owner, runner_id) WHERE ended_at IS NULL;
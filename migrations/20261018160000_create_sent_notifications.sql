@@ -0,0 +1,12 @@
+-- Notifications that were delivered, so recurring jobs alert at most once per period
+CREATE TABLE IF NOT EXISTS sent_notifications (
+    id BIGSERIAL PRIMARY KEY,
+    kind TEXT NOT NULL,
+    target TEXT NOT NULL,
+    period TEXT NOT NULL,
+    title TEXT NOT NULL,
+    message TEXT NOT NULL,
+    sinks INTEGER NOT NULL,
+    sent_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (kind, target, period)
+);
@@ -0,0 +1,9 @@
+-- WakaTime coding activity, one row per day
+CREATE TABLE IF NOT EXISTS waka_daily_summaries (
+    date DATE PRIMARY KEY,
+    total_seconds DOUBLE PRECISION NOT NULL,
+    languages JSONB NOT NULL DEFAULT '[]',
+    editors JSONB NOT NULL DEFAULT '[]',
+    projects JSONB NOT NULL DEFAULT '[]',
+    fetched_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,43 @@
+use std::future::{ready, Ready};
+
+use actix_web::{dev::Payload, http::header::AUTHORIZATION, FromRequest, HttpRequest};
+use envconfig::Envconfig;
+
+use crate::{config::Config, error::AppError};
+
+// Extractor that only succeeds when the request carries the configured `ADMIN_KEY`,
+// either as `Authorization: Bearer <key>` or `X-Admin-Key: <key>`
+pub struct Admin;
+
+impl FromRequest for Admin {
+    type Error = AppError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        ready(check_admin(req))
+    }
+}
+
+fn check_admin(req: &HttpRequest) -> Result<Admin, AppError> {
+    let config = Config::init_from_env()?;
+    if config.admin_key.is_empty() {
+        return Err(AppError::Unauthorized("Admin key not configured".to_string()));
+    }
+
+    let headers = req.headers();
+    let provided = headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .or_else(|| headers.get("X-Admin-Key").and_then(|value| value.to_str().ok()));
+
+    match provided {
+        Some(key) if constant_time_eq(key.as_bytes(), config.admin_key.as_bytes()) => Ok(Admin),
+        Some(_) => Err(AppError::Unauthorized("Invalid admin key".to_string())),
+        None => Err(AppError::Unauthorized("Missing admin key".to_string())),
+    }
+}
+
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
@@ -7,16 +7,8 @@ use crate::{
     config::Config,
     db::{postgres::PostgresManager, redis::RedisManager},
     error::AppError,
-    modules::{
-        default,
-        duolingo::handler::get_duo_user,
-        github::handler::{repos, runners},
-        health, index,
-        spotify::handler::{
-            authorize, callback, current, devices, playlists, queue, 
-            realtime_info, recently_played, top_artists, top_tracks
-        },
-    },
+    modules::{default, health, index, now::get_now},
+    provider::ProviderRegistry,
 };
 
 pub struct NullClient {
@@ -36,7 +28,11 @@ impl NullClient {
         let data = web::Data::new(Mutex::new(Self { postgres, redis }));
         // Load Client Config
         let config = Config::init_from_env()?;
-        
+
+        // Load Providers
+        let registry = web::Data::new(ProviderRegistry::load(&config));
+        registry.spawn_refreshers(web::Data::clone(&data));
+
         // Start HTTP Server
         let server = HttpServer::new(move || {
             let logger = Logger::default();
@@ -44,7 +40,9 @@ impl NullClient {
                 .wrap(logger)
                 .default_service(web::route().to(default))
                 .configure(Self::init)
+                .configure(|cfg| registry.configure(cfg))
                 .app_data(web::Data::clone(&data))
+                .app_data(web::Data::clone(&registry))
         })
         .bind((config.listen_host.clone(), config.listen_port))?;
         
@@ -56,26 +54,10 @@ impl NullClient {
         server.run().await.map_err(AppError::from)
     }
 
-    // Initialize Services
+    // Initialize General Services, providers mount their own
     pub fn init(cfg: &mut web::ServiceConfig) {
-        // General
         cfg.service(index);
         cfg.service(health);
-        //Spotify
-        cfg.service(current);
-        cfg.service(authorize);
-        cfg.service(callback);
-        cfg.service(realtime_info);
-        cfg.service(devices);
-        cfg.service(queue);
-        cfg.service(top_tracks);
-        cfg.service(top_artists);
-        cfg.service(recently_played);
-        cfg.service(playlists);
-        // Github
-        cfg.service(runners);
-        cfg.service(repos);
-        //Duolingo
-        cfg.service(get_duo_user);
+        cfg.service(get_now);
     }
 }
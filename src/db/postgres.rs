@@ -18,6 +18,9 @@ impl PostgresManager {
             .await?;
 
         info!("Connected to PostgreSQL database");
+
+        sqlx::migrate!().run(&pool).await?;
+        info!("Applied PostgreSQL migrations");
         Ok(Self { pool })
     }
 }
@@ -1,48 +1,204 @@
-use actix_web::{HttpResponse, ResponseError};
+use actix_web::{http::StatusCode, HttpResponse, ResponseError};
 use std::fmt;
 
+// Failures talking to the GitHub API
+#[derive(Debug)]
+pub enum GithubError {
+    // Token missing, invalid or lacking scope
+    Unauthorized(String),
+    // Unix time from `X-RateLimit-Reset` (or `Retry-After`) when known
+    RateLimited { reset: Option<i64> },
+    NotFound(String),
+    // GitHub answered, but not with the shape we expect
+    Schema(String),
+    // GitHub unreachable or returning 5xx
+    Upstream(String),
+}
+
+impl fmt::Display for GithubError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GithubError::Unauthorized(msg) => write!(f, "authentication failed: {}", msg),
+            GithubError::RateLimited { reset: Some(reset) } => {
+                write!(f, "rate limited until {}", reset)
+            }
+            GithubError::RateLimited { reset: None } => write!(f, "rate limited"),
+            GithubError::NotFound(msg) => write!(f, "not found: {}", msg),
+            GithubError::Schema(msg) => write!(f, "unexpected response: {}", msg),
+            GithubError::Upstream(msg) => write!(f, "upstream failure: {}", msg),
+        }
+    }
+}
+
+// Failures talking to the Spotify Web API
+#[derive(Debug)]
+pub enum SpotifyError {
+    // Seconds from `Retry-After` when Spotify sent it
+    RateLimited { retry_after: Option<u64> },
+    // Any other non-2xx answer, with Spotify's own error message
+    Status { status: u16, message: String },
+    // Not linked, token failures, nothing playing
+    Other(String),
+}
+
+impl fmt::Display for SpotifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpotifyError::RateLimited { retry_after: Some(secs) } => {
+                write!(f, "rate limited, retry after {}s", secs)
+            }
+            SpotifyError::RateLimited { retry_after: None } => write!(f, "rate limited"),
+            SpotifyError::Status { status, message } => write!(f, "{} ({})", message, status),
+            SpotifyError::Other(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+// Failures talking to the Duolingo API
+#[derive(Debug)]
+pub enum DuoError {
+    // Unknown username or a profile hidden from us
+    NotFound(String),
+    // Duolingo refused our DUO_API credentials
+    Unauthorized(String),
+    // Duolingo answered, but not with a profile we can read
+    Schema(String),
+    // Duolingo unreachable or returning an unexpected status
+    Upstream(String),
+}
+
+impl fmt::Display for DuoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DuoError::NotFound(msg) => write!(f, "not found: {}", msg),
+            DuoError::Unauthorized(msg) => write!(f, "authentication failed: {}", msg),
+            DuoError::Schema(msg) => write!(f, "unexpected response: {}", msg),
+            DuoError::Upstream(msg) => write!(f, "upstream failure: {}", msg),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum AppError {
     DatabaseError(sqlx::Error),
+    MigrationError(sqlx::migrate::MigrateError),
     RedisError(redis::RedisError),
     ConfigError(envconfig::Error),
     HttpError(reqwest::Error),
     JsonError(serde_json::Error),
     UrlEncodedError(serde_urlencoded::ser::Error),
     IoError(std::io::Error),
-    SpotifyError(String),
+    SpotifyError(SpotifyError),
+    GithubError(GithubError),
+    DuoError(DuoError),
+    WakaError(String),
+    BadRequest(String),
+    Unauthorized(String),
+    NotFound(String),
 }
 
 impl fmt::Display for AppError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             AppError::DatabaseError(err) => write!(f, "Database error: {}", err),
+            AppError::MigrationError(err) => write!(f, "Migration error: {}", err),
             AppError::RedisError(err) => write!(f, "Redis error: {}", err),
             AppError::ConfigError(err) => write!(f, "Configuration error: {}", err),
             AppError::HttpError(err) => write!(f, "HTTP error: {}", err),
             AppError::JsonError(err) => write!(f, "JSON error: {}", err),
             AppError::UrlEncodedError(err) => write!(f, "URL encoding error: {}", err),
             AppError::IoError(err) => write!(f, "IO error: {}", err),
-            AppError::SpotifyError(msg) => write!(f, "Spotify error: {}", msg),
+            AppError::SpotifyError(err) => write!(f, "Spotify error: {}", err),
+            AppError::GithubError(err) => write!(f, "GitHub error: {}", err),
+            AppError::DuoError(err) => write!(f, "Duolingo error: {}", err),
+            AppError::WakaError(msg) => write!(f, "WakaTime error: {}", msg),
+            AppError::BadRequest(msg) => write!(f, "Bad request: {}", msg),
+            AppError::Unauthorized(msg) => write!(f, "Unauthorized: {}", msg),
+            AppError::NotFound(msg) => write!(f, "Not found: {}", msg),
         }
     }
 }
 
 impl ResponseError for AppError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            AppError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            AppError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+            AppError::GithubError(err) => match err {
+                GithubError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+                GithubError::RateLimited { .. } => StatusCode::TOO_MANY_REQUESTS,
+                GithubError::NotFound(_) => StatusCode::NOT_FOUND,
+                GithubError::Schema(_) | GithubError::Upstream(_) => StatusCode::BAD_GATEWAY,
+            },
+            // Our credentials failing is an upstream problem, not the caller's
+            AppError::DuoError(err) => match err {
+                DuoError::NotFound(_) => StatusCode::NOT_FOUND,
+                DuoError::Unauthorized(_) | DuoError::Schema(_) | DuoError::Upstream(_) => {
+                    StatusCode::BAD_GATEWAY
+                }
+            },
+            AppError::SpotifyError(err) => match err {
+                SpotifyError::RateLimited { .. } => StatusCode::TOO_MANY_REQUESTS,
+                SpotifyError::Status { status: 404, .. } => StatusCode::NOT_FOUND,
+                SpotifyError::Status { .. } => StatusCode::BAD_GATEWAY,
+                SpotifyError::Other(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            },
+            AppError::WakaError(_) => StatusCode::BAD_GATEWAY,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
     fn error_response(&self) -> HttpResponse {
-        HttpResponse::InternalServerError().json(serde_json::json!({
+        let mut response = HttpResponse::build(self.status_code());
+        if let AppError::GithubError(GithubError::RateLimited { reset: Some(reset) }) = self {
+            let retry_after = (reset - chrono::Utc::now().timestamp()).max(0);
+            response.insert_header(("Retry-After", retry_after.to_string()));
+            return response.json(serde_json::json!({
+                "error": self.to_string(),
+                "reset": reset
+            }));
+        }
+        if let AppError::SpotifyError(SpotifyError::RateLimited { retry_after: Some(secs) }) = self {
+            response.insert_header(("Retry-After", secs.to_string()));
+        }
+        response.json(serde_json::json!({
             "error": self.to_string()
         }))
     }
 }
 
 // Conversion implementations
+impl From<GithubError> for AppError {
+    fn from(err: GithubError) -> Self {
+        AppError::GithubError(err)
+    }
+}
+
+impl From<SpotifyError> for AppError {
+    fn from(err: SpotifyError) -> Self {
+        AppError::SpotifyError(err)
+    }
+}
+
+impl From<DuoError> for AppError {
+    fn from(err: DuoError) -> Self {
+        AppError::DuoError(err)
+    }
+}
+
 impl From<sqlx::Error> for AppError {
     fn from(err: sqlx::Error) -> Self {
         AppError::DatabaseError(err)
     }
 }
 
+impl From<sqlx::migrate::MigrateError> for AppError {
+    fn from(err: sqlx::migrate::MigrateError) -> Self {
+        AppError::MigrationError(err)
+    }
+}
+
 impl From<redis::RedisError> for AppError {
     fn from(err: redis::RedisError) -> Self {
         AppError::RedisError(err)
@@ -2,11 +2,14 @@ use client::NullClient;
 use dotenvy::dotenv;
 use log::error;
 // Import Modules
+pub mod auth;
 pub mod client;
 pub mod config;
 pub mod db;
 pub mod error;
 pub mod modules;
+pub mod notify;
+pub mod provider;
 
 // Main Application Loop
 #[rustfmt::skip]
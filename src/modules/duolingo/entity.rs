@@ -1,30 +1,202 @@
+use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize)]
+// Duolingo's legacy profile; any field may be missing or null depending on privacy settings
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct User {
-    username: String,
-    bio: String,
-    id: u32,
-    learning_language_string: String,
-    created: String,
-    admin: bool,
-    email: Option<String>,
-    invite_url: String,
-    fullname: String,
-    avatar: String,
-    ui_language: String,
-    languages: Vec<Language>,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
+    #[serde(deserialize_with = "nullable")]
+    pub username: String,
+    #[serde(deserialize_with = "nullable")]
+    pub bio: String,
+    #[serde(deserialize_with = "nullable")]
+    pub id: u32,
+    #[serde(deserialize_with = "nullable")]
+    pub learning_language_string: String,
+    #[serde(deserialize_with = "nullable")]
+    pub created: String,
+    #[serde(deserialize_with = "nullable")]
+    pub admin: bool,
+    pub email: Option<String>,
+    #[serde(deserialize_with = "nullable")]
+    pub invite_url: String,
+    #[serde(deserialize_with = "nullable")]
+    pub fullname: String,
+    #[serde(deserialize_with = "nullable")]
+    pub avatar: String,
+    #[serde(deserialize_with = "nullable")]
+    pub ui_language: String,
+    #[serde(deserialize_with = "nullable")]
+    pub languages: Vec<Language>,
+    // Duolingo's own flag for whether today's lesson is done
+    pub streak_extended_today: Option<bool>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Language {
-    streak: u32,
-    language_string: String,
-    points: u32,
-    learning: bool,
-    language: String,
-    level: u32,
-    current_learning: bool,
-    sentences_translated: u32,
-    to_next_level: u32,
+    #[serde(deserialize_with = "nullable")]
+    pub streak: u32,
+    #[serde(deserialize_with = "nullable")]
+    pub language_string: String,
+    #[serde(deserialize_with = "nullable")]
+    pub points: u32,
+    #[serde(deserialize_with = "nullable")]
+    pub learning: bool,
+    #[serde(deserialize_with = "nullable")]
+    pub language: String,
+    #[serde(deserialize_with = "nullable")]
+    pub level: u32,
+    #[serde(deserialize_with = "nullable")]
+    pub current_learning: bool,
+    #[serde(deserialize_with = "nullable")]
+    pub sentences_translated: u32,
+    #[serde(deserialize_with = "nullable")]
+    pub to_next_level: u32,
+}
+
+// Treat an explicit `null` like a missing field
+fn nullable<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Default + Deserialize<'de>,
+{
+    Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+// One language on one day, taken from that day's latest snapshot
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct LanguageDay {
+    #[serde(skip)]
+    pub language: String,
+    #[serde(skip)]
+    pub language_string: String,
+    pub date: NaiveDate,
+    pub points: i64,
+    // Versus the previous recorded day; null for the first day on record
+    pub xp_gained: Option<i64>,
+    pub level: i32,
+    pub levels_gained: Option<i32>,
+    pub to_next_level: i64,
+    pub streak: i32,
+    pub sentences_translated: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LanguageHistory {
+    pub language: String,
+    pub language_string: String,
+    pub xp_gained: i64,
+    pub levels_gained: i32,
+    pub days: Vec<LanguageDay>,
+}
+
+impl LanguageHistory {
+    // Group rows ordered by language then date
+    pub fn group(rows: Vec<LanguageDay>) -> Vec<Self> {
+        let mut history: Vec<Self> = Vec::new();
+        for row in rows {
+            match history.last_mut() {
+                Some(last) if last.language == row.language => last.push(row),
+                _ => {
+                    let mut entry = Self {
+                        language: row.language.clone(),
+                        language_string: row.language_string.clone(),
+                        xp_gained: 0,
+                        levels_gained: 0,
+                        days: Vec::new(),
+                    };
+                    entry.push(row);
+                    history.push(entry);
+                }
+            }
+        }
+        history
+    }
+
+    fn push(&mut self, day: LanguageDay) {
+        self.xp_gained += day.xp_gained.unwrap_or(0);
+        self.levels_gained += day.levels_gained.unwrap_or(0);
+        self.days.push(day);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LeaderboardMetric {
+    Points,
+    Streak,
+    Level,
+}
+
+impl LeaderboardMetric {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "points" | "xp" => Some(Self::Points),
+            "streak" => Some(Self::Streak),
+            "level" => Some(Self::Level),
+            _ => None,
+        }
+    }
+
+    pub fn value(&self, entry: &LeaderboardEntry) -> i64 {
+        match self {
+            Self::Points => entry.points,
+            Self::Streak => i64::from(entry.streak),
+            Self::Level => i64::from(entry.level),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct LeaderboardEntry {
+    pub rank: usize,
+    pub username: String,
+    pub fullname: Option<String>,
+    pub avatar: Option<String>,
+    pub points: i64,
+    pub streak: u32,
+    pub level: u32,
+    // XP earned since the snapshot closest to a week ago; null until history exists
+    pub weekly_xp: Option<i64>,
+}
+
+impl LeaderboardEntry {
+    // Scores for one language, or across all languages (total XP, best streak, highest level)
+    pub fn from_user(name: &str, user: &User, language: Option<&str>) -> Option<Self> {
+        let languages: Vec<&Language> = user
+            .languages
+            .iter()
+            .filter(|entry| language.is_none_or(|code| entry.language.eq_ignore_ascii_case(code)))
+            .filter(|entry| entry.learning || language.is_some())
+            .collect();
+        if languages.is_empty() {
+            return None;
+        }
+        Some(Self {
+            rank: 0,
+            username: name.to_string(),
+            fullname: Some(user.fullname.clone()).filter(|fullname| !fullname.is_empty()),
+            avatar: Some(user.avatar.clone()).filter(|avatar| !avatar.is_empty()),
+            points: languages.iter().map(|entry| i64::from(entry.points)).sum(),
+            streak: languages.iter().map(|entry| entry.streak).max().unwrap_or(0),
+            level: languages.iter().map(|entry| entry.level).max().unwrap_or(0),
+            weekly_xp: None,
+        })
+    }
+}
+
+// Points recorded for a language in the snapshot a weekly delta is measured from
+#[derive(Debug, sqlx::FromRow)]
+pub struct PointsBaseline {
+    pub username: String,
+    pub language: String,
+    pub points: i64,
+}
+
+// Streak recorded for a language by the first snapshot of the current UTC day
+#[derive(Debug, sqlx::FromRow)]
+pub struct StartOfDayStreak {
+    pub language: String,
+    pub streak: i32,
 }
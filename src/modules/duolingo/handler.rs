@@ -1,15 +1,39 @@
+use std::collections::{HashMap, HashSet};
+
 use actix_web::{get, web, HttpResponse};
-use envconfig::Envconfig;
-use log::info;
+use serde::Deserialize;
+use serde_json::json;
 use tokio::sync::Mutex;
 
 use crate::{
     client::NullClient,
-    config::Config,
     error::AppError,
-    modules::duolingo::{entity::User, DuoManager},
+    modules::duolingo::{
+        entity::{LanguageHistory, LeaderboardEntry, LeaderboardMetric},
+        DuoManager, DuoRepository,
+    },
 };
 
+const MAX_LEADERBOARD_USERS: usize = 25;
+
+#[derive(Debug, Deserialize)]
+pub struct HistoryQuery {
+    #[serde(default = "default_history_days")]
+    pub days: i32,
+}
+
+fn default_history_days() -> i32 {
+    30
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LeaderboardQuery {
+    // Comma-separated usernames, defaults to DUO_USERS
+    pub users: Option<String>,
+    pub metric: Option<String>,
+    pub language: Option<String>,
+}
+
 #[get("/v1/duo/stats/{name}")]
 async fn get_duo_user(
     path: web::Path<String>,
@@ -17,29 +41,157 @@ async fn get_duo_user(
 ) -> Result<HttpResponse, AppError> {
     let data = state.lock().await;
     let mut redis = data.redis.clone();
-    let config = Config::init_from_env()?;
-    let name = path.into_inner();
+    let pool = data.postgres.pool.clone();
+    // Duolingo usernames are case-insensitive; everything downstream keys on lowercase
+    let name = path.into_inner().to_lowercase();
     
     drop(data); // Release the lock early
     
-    if DuoManager::check_duo_stats(&mut redis, &name).await? {
-        info!("Retrieved cached stats for user: {}", &name);
-        let user = DuoManager::get_duo_stats(&mut redis, &name).await?;
-        Ok(HttpResponse::Ok().json(user))
-    } else {
-        info!("Fetching fresh stats for user: {}", &name);
-        let response = reqwest::Client::new()
-            .get(format!("https://www.duolingo.com/users/{}", name))
-            .header("Authorization", config.duo_api)
-            .send()
-            .await?
-            .text()
-            .await?;
-
-        let user: User = serde_json::from_str(&response)?;
-        
-        // Store stats in cache
-        DuoManager::store_duo_stats(&mut redis, &name, &user).await?;
-        Ok(HttpResponse::Ok().json(user))
+    let user = DuoManager::get_duo_user(&mut redis, &pool, &name).await?;
+    Ok(HttpResponse::Ok().json(user))
+}
+
+#[get("/v1/duo/stats/{name}/history")]
+async fn get_duo_history(
+    path: web::Path<String>,
+    query: web::Query<HistoryQuery>,
+    state: web::Data<Mutex<NullClient>>,
+) -> Result<HttpResponse, AppError> {
+    let data = state.lock().await;
+    let pool = data.postgres.pool.clone();
+    let name = path.into_inner().to_lowercase();
+
+    drop(data); // Release the lock early
+
+    if !(1..=365).contains(&query.days) {
+        return Err(AppError::BadRequest(
+            "days must be between 1 and 365".to_string(),
+        ));
+    }
+
+    let rows = DuoRepository::language_history(&pool, &name, query.days).await?;
+    if rows.is_empty() {
+        return Err(AppError::NotFound(format!(
+            "No Duolingo history recorded for {}",
+            name
+        )));
+    }
+
+    Ok(HttpResponse::Ok().json(json!({
+        "success": true,
+        "username": name,
+        "days": query.days,
+        "data": LanguageHistory::group(rows)
+    })))
+}
+
+#[get("/v1/duo/leaderboard")]
+async fn get_duo_leaderboard(
+    query: web::Query<LeaderboardQuery>,
+    state: web::Data<Mutex<NullClient>>,
+) -> Result<HttpResponse, AppError> {
+    let data = state.lock().await;
+    let mut redis = data.redis.clone();
+    let pool = data.postgres.pool.clone();
+
+    drop(data); // Release the lock early
+
+    let metric = match query.metric.as_deref() {
+        None => LeaderboardMetric::Points,
+        Some(value) => LeaderboardMetric::parse(value).ok_or_else(|| {
+            AppError::BadRequest(format!(
+                "Unknown metric '{}', expected points, streak or level",
+                value
+            ))
+        })?,
+    };
+    let language = query.language.as_deref().map(str::trim).filter(|code| !code.is_empty());
+
+    let mut names: Vec<String> = match query.users.as_deref() {
+        Some(users) => users
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(str::to_lowercase)
+            .collect(),
+        None => DuoManager::tracked_users()?,
+    };
+    let mut seen = HashSet::new();
+    names.retain(|name| seen.insert(name.clone()));
+    if names.is_empty() {
+        return Err(AppError::BadRequest(
+            "No users given and DUO_USERS is empty".to_string(),
+        ));
+    }
+    if names.len() > MAX_LEADERBOARD_USERS {
+        return Err(AppError::BadRequest(format!(
+            "At most {} users can be compared",
+            MAX_LEADERBOARD_USERS
+        )));
+    }
+
+    let mut entries = Vec::new();
+    let mut skipped = Vec::new();
+    let mut current: HashMap<(String, String), i64> = HashMap::new();
+    for (name, result) in DuoManager::get_duo_users(&mut redis, &pool, &names).await {
+        let user = match result {
+            Ok(user) => user,
+            Err(e) => {
+                skipped.push(json!({"username": name, "reason": e.to_string()}));
+                continue;
+            }
+        };
+        match LeaderboardEntry::from_user(&name, &user, language) {
+            Some(entry) => {
+                // Same courses as the score: ones still being learned, or the one asked for
+                let counted = user.languages.iter().filter(|learned| {
+                    learned.learning
+                        || language.is_some_and(|code| learned.language.eq_ignore_ascii_case(code))
+                });
+                for learned in counted {
+                    current.insert((name.clone(), learned.language.clone()), i64::from(learned.points));
+                }
+                entries.push(entry);
+            }
+            None => skipped.push(json!({
+                "username": name,
+                "reason": format!("Not learning {}", language.unwrap_or("any language"))
+            })),
+        }
+    }
+
+    // Weekly XP compares the live points against stored snapshots
+    let mut weekly: HashMap<String, i64> = HashMap::new();
+    for baseline in DuoRepository::weekly_baselines(&pool, &names).await? {
+        if language.is_some_and(|code| !baseline.language.eq_ignore_ascii_case(code)) {
+            continue;
+        }
+        if let Some(points) = current.get(&(baseline.username.clone(), baseline.language.clone())) {
+            *weekly.entry(baseline.username).or_default() += (points - baseline.points).max(0);
+        }
+    }
+    for entry in &mut entries {
+        entry.weekly_xp = weekly.get(&entry.username).copied();
     }
+
+    entries.sort_by(|a, b| {
+        metric
+            .value(b)
+            .cmp(&metric.value(a))
+            .then_with(|| b.points.cmp(&a.points))
+            .then_with(|| a.username.cmp(&b.username))
+    });
+    // Equal scores share a rank
+    for index in 0..entries.len() {
+        let tied = index > 0 && metric.value(&entries[index]) == metric.value(&entries[index - 1]);
+        entries[index].rank = if tied { entries[index - 1].rank } else { index + 1 };
+    }
+
+    Ok(HttpResponse::Ok().json(json!({
+        "success": true,
+        "metric": metric,
+        "language": language,
+        "data": entries,
+        "skipped": skipped
+    })))
 }
@@ -1,8 +1,19 @@
+use envconfig::Envconfig;
+use futures::{stream, StreamExt};
+use log::{error, info};
 use redis::{aio::ConnectionManager, AsyncCommands};
+use reqwest::StatusCode;
+use sqlx::{Pool, Postgres};
 
-use crate::{db::redis::RedisManager, error::AppError};
+use crate::{
+    config::Config,
+    db::redis::RedisManager,
+    error::{AppError, DuoError},
+};
 
-use super::entity::User;
+use super::{entity::User, DuoRepository};
+
+const USER_CONCURRENCY: usize = 4;
 
 #[derive(Clone)]
 pub struct DuoManager;
@@ -43,4 +54,147 @@ impl DuoManager {
 
         Ok(())
     }
+
+    // Usernames snapshotted in the background, lowercased like every other Duolingo key
+    pub fn tracked_users() -> Result<Vec<String>, AppError> {
+        let config = Config::init_from_env()?;
+        Ok(config
+            .duo_users
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(str::to_lowercase)
+            .collect())
+    }
+
+    // Fetch straight from Duolingo, bypassing the cache
+    pub async fn fetch_duo_user(name: &str) -> Result<User, AppError> {
+        let config = Config::init_from_env()?;
+        let response = reqwest::Client::new()
+            .get(format!("https://www.duolingo.com/users/{}", name))
+            .header("Authorization", config.duo_api)
+            .send()
+            .await
+            .map_err(|e| DuoError::Upstream(e.to_string()))?;
+
+        let status = response.status();
+        match status {
+            StatusCode::NOT_FOUND => {
+                return Err(DuoError::NotFound(format!("Duolingo user {} does not exist", name)).into())
+            }
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+                return Err(DuoError::Unauthorized(format!(
+                    "Duolingo returned {}, check DUO_API",
+                    status
+                ))
+                .into())
+            }
+            status if !status.is_success() => {
+                return Err(DuoError::Upstream(format!("Duolingo returned {}", status)).into())
+            }
+            _ => {}
+        }
+
+        let body = response
+            .text()
+            .await
+            .map_err(|e| DuoError::Upstream(e.to_string()))?;
+        let user: User = serde_json::from_str(&body)
+            .map_err(|e| DuoError::Schema(format!("unreadable profile for {}: {}", name, e)))?;
+
+        // Private and deleted profiles come back as an empty object
+        if user.username.is_empty() && user.languages.is_empty() {
+            return Err(DuoError::NotFound(format!(
+                "Duolingo user {} does not exist or is private",
+                name
+            ))
+            .into());
+        }
+        Ok(user)
+    }
+
+    // Cached user, fetching and snapshotting a fresh copy on a miss
+    pub async fn get_duo_user(
+        redis: &mut RedisManager,
+        pool: &Pool<Postgres>,
+        name: &str,
+    ) -> Result<User, AppError> {
+        if Self::check_duo_stats(redis, name).await? {
+            info!("Retrieved cached stats for user: {}", name);
+            return Self::get_duo_stats(redis, name).await;
+        }
+
+        let missing_key = format!("duo:missing:{}", name);
+        if let Some(reason) = redis.connection.get::<_, Option<String>>(&missing_key).await? {
+            return Err(DuoError::NotFound(reason).into());
+        }
+
+        info!("Fetching fresh stats for user: {}", name);
+        let user = match Self::fetch_duo_user(name).await {
+            Ok(user) => user,
+            Err(AppError::DuoError(DuoError::NotFound(reason))) => {
+                // Remember unknown users briefly so typos don't hammer Duolingo
+                let config = Config::init_from_env()?;
+                if config.duo_not_found_ttl > 0 {
+                    redis::cmd("SET")
+                        .arg(&missing_key)
+                        .arg(&reason)
+                        .arg("EX")
+                        .arg(config.duo_not_found_ttl)
+                        .query_async::<ConnectionManager, ()>(&mut redis.connection)
+                        .await?;
+                }
+                return Err(DuoError::NotFound(reason).into());
+            }
+            Err(e) => return Err(e),
+        };
+
+        // Store stats in cache
+        Self::store_duo_stats(redis, name, &user).await?;
+        if let Err(e) = DuoRepository::record_user(pool, name, &user).await {
+            error!("Failed to record Duolingo snapshot for {}: {}", name, e);
+        }
+        Ok(user)
+    }
+
+    // Several users through the cache, in request order, each with its own result
+    pub async fn get_duo_users(
+        redis: &mut RedisManager,
+        pool: &Pool<Postgres>,
+        names: &[String],
+    ) -> Vec<(String, Result<User, AppError>)> {
+        stream::iter(names.iter().cloned())
+            .map(|name| {
+                let mut redis = redis.clone();
+                let pool = pool.clone();
+                async move {
+                    let user = Self::get_duo_user(&mut redis, &pool, &name).await;
+                    (name, user)
+                }
+            })
+            .buffered(USER_CONCURRENCY)
+            .collect()
+            .await
+    }
+
+    // Whether the streak went up today: Duolingo's own flag when it sends one, otherwise a rise
+    // over today's first snapshot; `None` when neither is available
+    pub async fn streak_extended_today(
+        pool: &Pool<Postgres>,
+        name: &str,
+        user: &User,
+    ) -> Result<Option<bool>, AppError> {
+        if user.streak_extended_today.is_some() {
+            return Ok(user.streak_extended_today);
+        }
+        let baseline = DuoRepository::streaks_at_start_of_today(pool, name).await?;
+        if baseline.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(user.languages.iter().filter(|language| language.learning).any(|language| {
+            baseline.iter().any(|start| {
+                start.language == language.language && i64::from(language.streak) > i64::from(start.streak)
+            })
+        })))
+    }
 }
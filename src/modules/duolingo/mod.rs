@@ -1,5 +1,9 @@
 pub mod entity;
 pub mod handler;
 pub mod manager;
+pub mod provider;
+pub mod repository;
 
 pub use manager::DuoManager;
+pub use provider::DuoProvider;
+pub use repository::DuoRepository;
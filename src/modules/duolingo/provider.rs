@@ -0,0 +1,193 @@
+use std::time::Duration;
+
+use actix_web::web;
+use async_trait::async_trait;
+use chrono::{Timelike, Utc};
+use envconfig::Envconfig;
+use log::{error, info};
+use redis::{aio::ConnectionManager, AsyncCommands};
+use serde_json::{json, Value};
+use sqlx::{Pool, Postgres};
+use tokio::sync::Mutex;
+
+use crate::{
+    client::NullClient,
+    config::Config,
+    db::redis::RedisManager,
+    error::AppError,
+    notify::{Notification, Notifier},
+    provider::Provider,
+};
+
+use super::{
+    handler::{get_duo_history, get_duo_leaderboard, get_duo_user},
+    DuoManager, DuoRepository,
+};
+
+const STREAK_NOTIFICATION: &str = "duo_streak_at_risk";
+// The per-day "checked" marker only needs to outlive the day it covers
+const STREAK_CHECKED_TTL: u64 = 2 * 24 * 60 * 60;
+
+pub struct DuoProvider;
+
+#[async_trait]
+impl Provider for DuoProvider {
+    fn name(&self) -> &'static str {
+        "duolingo"
+    }
+
+    fn routes(&self, cfg: &mut web::ServiceConfig) {
+        cfg.service(get_duo_leaderboard);
+        cfg.service(get_duo_history);
+        cfg.service(get_duo_user);
+    }
+
+    fn refresh_interval(&self) -> Option<Duration> {
+        let config = Config::init_from_env().ok()?;
+        if config.duo_users.trim().is_empty() {
+            return None;
+        }
+        match config.duo_snapshot_interval {
+            0 => None,
+            secs => Some(Duration::from_secs(secs.max(60))),
+        }
+    }
+
+    // Make sure every tracked user has a snapshot for the current day, then remind in the
+    // evening about streaks that haven't been extended
+    async fn refresh(&self, state: &web::Data<Mutex<NullClient>>) -> Result<(), AppError> {
+        let config = Config::init_from_env()?;
+        let data = state.lock().await;
+        let mut redis = data.redis.clone();
+        let pool = data.postgres.pool.clone();
+        drop(data);
+
+        // A failure for one user is logged and doesn't hold up the others
+        for name in DuoManager::tracked_users()? {
+            if let Err(e) = Self::snapshot_user(&mut redis, &pool, &name).await {
+                error!("Failed to snapshot Duolingo stats for {}: {}", name, e);
+            }
+        }
+
+        let now = Utc::now();
+        if now.hour() < config.duo_streak_check_hour {
+            return Ok(());
+        }
+        let today = now.date_naive().to_string();
+        for name in DuoManager::tracked_users()? {
+            if let Err(e) = Self::check_streak(&mut redis, &pool, &name, &today).await {
+                error!("Failed to check Duolingo streak for {}: {}", name, e);
+            }
+        }
+        Ok(())
+    }
+
+    // Streak of every tracked user and whether it went up today
+    async fn now(&self, state: &web::Data<Mutex<NullClient>>) -> Result<Option<Value>, AppError> {
+        let data = state.lock().await;
+        let mut redis = data.redis.clone();
+        let pool = data.postgres.pool.clone();
+        drop(data);
+
+        let names = DuoManager::tracked_users()?;
+        let mut learners = Vec::new();
+        for (name, result) in DuoManager::get_duo_users(&mut redis, &pool, &names).await {
+            let user = match result {
+                Ok(user) => user,
+                Err(e) => {
+                    learners.push(json!({ "username": name, "error": e.to_string() }));
+                    continue;
+                }
+            };
+            let streak = user.languages.iter().map(|language| language.streak).max().unwrap_or(0);
+            // A failed lookup only blanks this learner's flag
+            match DuoManager::streak_extended_today(&pool, &name, &user).await {
+                Ok(extended) => learners.push(json!({
+                    "username": name,
+                    "learning": user.learning_language_string,
+                    "streak": streak,
+                    "extended_today": extended
+                })),
+                Err(e) => learners.push(json!({
+                    "username": name,
+                    "learning": user.learning_language_string,
+                    "streak": streak,
+                    "extended_today": null,
+                    "error": e.to_string()
+                })),
+            }
+        }
+        Ok(Some(json!(learners)))
+    }
+}
+
+impl DuoProvider {
+    // Record today's snapshot unless one already exists
+    async fn snapshot_user(
+        redis: &mut RedisManager,
+        pool: &Pool<Postgres>,
+        name: &str,
+    ) -> Result<(), AppError> {
+        if DuoRepository::has_snapshot_today(pool, name).await? {
+            return Ok(());
+        }
+        let user = DuoManager::fetch_duo_user(name).await?;
+        DuoManager::store_duo_stats(redis, name, &user).await?;
+        DuoRepository::record_user(pool, name, &user).await?;
+        info!("Recorded daily Duolingo snapshot for {}", name);
+        Ok(())
+    }
+
+    // Remind when today's lesson isn't done yet; runs at most once per user per day
+    async fn check_streak(
+        redis: &mut RedisManager,
+        pool: &Pool<Postgres>,
+        name: &str,
+        today: &str,
+    ) -> Result<(), AppError> {
+        let checked_key = format!("duo:streak_checked:{}:{}", name, today);
+        let checked: bool = redis.connection.exists(&checked_key).await?;
+        if checked || Notifier::already_sent(pool, STREAK_NOTIFICATION, name, today).await? {
+            return Ok(());
+        }
+
+        // Always check live data; the cached copy may predate today's lesson
+        let user = DuoManager::fetch_duo_user(name).await?;
+        DuoManager::store_duo_stats(redis, name, &user).await?;
+        let extended = DuoManager::streak_extended_today(pool, name, &user).await?;
+        let streak = user
+            .languages
+            .iter()
+            .filter(|language| language.learning)
+            .map(|language| language.streak)
+            .max()
+            .unwrap_or(0);
+
+        // Nothing to lose without a streak, and nothing to decide without data
+        if extended == Some(false) && streak > 0 {
+            let notification = Notification {
+                kind: STREAK_NOTIFICATION.to_string(),
+                title: format!("{}'s Duolingo streak is at risk", name),
+                message: format!(
+                    "{} hasn't extended their {}-day streak today. Finish a lesson before midnight to keep it.",
+                    name, streak
+                ),
+            };
+            // Left unmarked when every sink failed, so the next run retries
+            if !Notifier::send_once(pool, &notification, name, today).await? {
+                return Ok(());
+            }
+            info!("Sent streak reminder for {}", name);
+        }
+
+        DuoRepository::record_user(pool, name, &user).await?;
+        redis::cmd("SET")
+            .arg(&checked_key)
+            .arg(1)
+            .arg("EX")
+            .arg(STREAK_CHECKED_TTL)
+            .query_async::<ConnectionManager, ()>(&mut redis.connection)
+            .await?;
+        Ok(())
+    }
+}
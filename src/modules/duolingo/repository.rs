@@ -0,0 +1,138 @@
+use sqlx::{Pool, Postgres};
+
+use crate::error::AppError;
+
+use super::entity::{LanguageDay, PointsBaseline, StartOfDayStreak, User};
+
+#[derive(Clone)]
+pub struct DuoRepository;
+
+impl DuoRepository {
+    // Store a freshly fetched user along with one row per language
+    pub async fn record_user(pool: &Pool<Postgres>, name: &str, user: &User) -> Result<(), AppError> {
+        let mut tx = pool.begin().await?;
+
+        let snapshot_id: i64 = sqlx::query_scalar(
+            "INSERT INTO duo_user_snapshots (username, duo_id, fullname, learning_language)
+             VALUES ($1, $2, $3, $4)
+             RETURNING id",
+        )
+        .bind(name)
+        .bind(i64::from(user.id))
+        .bind(&user.fullname)
+        .bind(&user.learning_language_string)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        for language in &user.languages {
+            sqlx::query(
+                "INSERT INTO duo_language_snapshots (
+                    snapshot_id, username, language, language_string, learning, current_learning,
+                    points, level, streak, sentences_translated, to_next_level
+                 ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)",
+            )
+            .bind(snapshot_id)
+            .bind(name)
+            .bind(&language.language)
+            .bind(&language.language_string)
+            .bind(language.learning)
+            .bind(language.current_learning)
+            .bind(i64::from(language.points))
+            .bind(language.level as i32)
+            .bind(language.streak as i32)
+            .bind(i64::from(language.sentences_translated))
+            .bind(i64::from(language.to_next_level))
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn has_snapshot_today(pool: &Pool<Postgres>, name: &str) -> Result<bool, AppError> {
+        let exists: bool = sqlx::query_scalar(
+            "SELECT EXISTS (
+                SELECT 1 FROM duo_user_snapshots
+                WHERE username = $1
+                  AND fetched_at >= date_trunc('day', NOW() AT TIME ZONE 'UTC') AT TIME ZONE 'UTC'
+             )",
+        )
+        .bind(name)
+        .fetch_one(pool)
+        .await?;
+        Ok(exists)
+    }
+
+    // Latest snapshot per language per UTC day over the last `days` days, with gains versus the
+    // previous recorded day (the day before the window serves as the baseline)
+    pub async fn language_history(
+        pool: &Pool<Postgres>,
+        name: &str,
+        days: i32,
+    ) -> Result<Vec<LanguageDay>, AppError> {
+        let rows = sqlx::query_as::<_, LanguageDay>(
+            "WITH daily AS (
+                SELECT DISTINCT ON (language, date)
+                    language, language_string, (fetched_at AT TIME ZONE 'UTC')::date AS date,
+                    points, level, to_next_level, streak, sentences_translated
+                FROM duo_language_snapshots
+                WHERE username = $1
+                  AND fetched_at >= ((NOW() AT TIME ZONE 'UTC')::date - $2) AT TIME ZONE 'UTC'
+                ORDER BY language, date, fetched_at DESC
+             ), ranked AS (
+                SELECT *,
+                    points - LAG(points) OVER w AS xp_gained,
+                    level - LAG(level) OVER w AS levels_gained
+                FROM daily
+                WINDOW w AS (PARTITION BY language ORDER BY date)
+             )
+             SELECT language, language_string, date, points, xp_gained, level, levels_gained,
+                    to_next_level, streak, sentences_translated
+             FROM ranked
+             WHERE date > (NOW() AT TIME ZONE 'UTC')::date - $2
+             ORDER BY language, date",
+        )
+        .bind(name)
+        .bind(days)
+        .fetch_all(pool)
+        .await?;
+        Ok(rows)
+    }
+
+    // Per language, the latest snapshot at least a week old, or the oldest one if history is younger
+    pub async fn weekly_baselines(
+        pool: &Pool<Postgres>,
+        names: &[String],
+    ) -> Result<Vec<PointsBaseline>, AppError> {
+        let rows = sqlx::query_as::<_, PointsBaseline>(
+            "SELECT DISTINCT ON (username, language) username, language, points
+             FROM duo_language_snapshots
+             WHERE username = ANY($1)
+             ORDER BY username, language,
+                      GREATEST(fetched_at, NOW() - INTERVAL '7 days'), fetched_at DESC",
+        )
+        .bind(names)
+        .fetch_all(pool)
+        .await?;
+        Ok(rows)
+    }
+
+    // Taken after the previous day ended, so a later lesson shows up as a rise
+    pub async fn streaks_at_start_of_today(
+        pool: &Pool<Postgres>,
+        name: &str,
+    ) -> Result<Vec<StartOfDayStreak>, AppError> {
+        let rows = sqlx::query_as::<_, StartOfDayStreak>(
+            "SELECT DISTINCT ON (language) language, streak
+             FROM duo_language_snapshots
+             WHERE username = $1
+               AND fetched_at >= date_trunc('day', NOW() AT TIME ZONE 'UTC') AT TIME ZONE 'UTC'
+             ORDER BY language, fetched_at ASC",
+        )
+        .bind(name)
+        .fetch_all(pool)
+        .await?;
+        Ok(rows)
+    }
+}
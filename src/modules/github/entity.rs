@@ -1,6 +1,59 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OwnerKind {
+    Org,
+    User,
+}
+
+// An allow-listed organization or personal account
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GithubOwner {
+    pub login: String,
+    pub kind: OwnerKind,
+}
+
+// A GitHub response kept in Redis along with its validators
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheEntry<T> {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    // Next page from the `Link` header, for paginated listings
+    #[serde(default)]
+    pub next: Option<String>,
+    // Unix time GitHub last confirmed this body
+    pub fetched_at: i64,
+    pub body: T,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CacheStatus {
+    // Served from Redis without contacting GitHub
+    Hit,
+    // GitHub answered 304 Not Modified
+    Revalidated,
+    // Fetched a new body from GitHub
+    Miss,
+}
+
+// Cache details surfaced in the response envelope
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheInfo {
+    pub status: CacheStatus,
+    pub age: i64,
+    pub fetched_at: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct Cached<T> {
+    pub data: T,
+    pub next: Option<String>,
+    pub cache: CacheInfo,
+}
+
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RunnerResponse {
@@ -31,6 +84,56 @@ pub struct Label {
 
 pub type RepoResponse = Vec<Root2>;
 
+// The fields of a repository worth returning from the listing endpoint
+#[derive(Debug, Clone, Serialize)]
+pub struct RepoSummary {
+    pub id: i64,
+    pub name: String,
+    pub full_name: String,
+    pub description: Option<String>,
+    pub html_url: String,
+    pub homepage: Option<String>,
+    pub language: Option<String>,
+    pub topics: Vec<String>,
+    pub license: Option<String>,
+    pub private: bool,
+    pub fork: bool,
+    pub archived: bool,
+    pub stargazers_count: i64,
+    pub forks_count: i64,
+    pub open_issues_count: i64,
+    pub default_branch: String,
+    pub created_at: String,
+    pub updated_at: String,
+    pub pushed_at: String,
+}
+
+impl From<&Root2> for RepoSummary {
+    fn from(repo: &Root2) -> Self {
+        Self {
+            id: repo.id,
+            name: repo.name.clone(),
+            full_name: repo.full_name.clone(),
+            description: repo.description.clone(),
+            html_url: repo.html_url.clone(),
+            homepage: repo.homepage.clone().filter(|homepage| !homepage.is_empty()),
+            language: repo.language.clone(),
+            topics: repo.topics.clone(),
+            license: repo.license.as_ref().map(|license| license.spdx_id.clone()),
+            private: repo.private,
+            fork: repo.fork,
+            archived: repo.archived,
+            stargazers_count: repo.stargazers_count,
+            forks_count: repo.forks_count,
+            open_issues_count: repo.open_issues_count,
+            default_branch: repo.default_branch.clone(),
+            created_at: repo.created_at.clone(),
+            updated_at: repo.updated_at.clone(),
+            pushed_at: repo.pushed_at.clone(),
+        }
+    }
+}
+
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Root2 {
@@ -175,6 +278,8 @@ pub struct Root2 {
     pub watchers: i64,
     #[serde(rename = "default_branch")]
     pub default_branch: String,
+    // Only present when the token can see the repository's permissions
+    #[serde(default)]
     pub permissions: Permissions,
 }
 
@@ -237,3 +342,629 @@ pub struct Permissions {
     pub triage: bool,
     pub pull: bool,
 }
+
+// GraphQL `user` payload for the profile query
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProfileData {
+    pub user: Option<GqlUser>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GqlUser {
+    pub login: String,
+    pub name: Option<String>,
+    pub bio: Option<String>,
+    pub avatar_url: String,
+    pub url: String,
+    pub company: Option<String>,
+    pub location: Option<String>,
+    pub website_url: Option<String>,
+    pub created_at: String,
+    pub followers: GqlCount,
+    pub following: GqlCount,
+    pub contributions_collection: GqlContributions,
+    pub pinned_items: GqlNodes<GqlPinnedRepo>,
+    pub repositories: GqlRepositories,
+    pub open_pull_requests: GqlCount,
+    pub open_issues: GqlCount,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GqlCount {
+    pub total_count: i64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GqlNodes<T> {
+    pub nodes: Vec<T>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GqlContributions {
+    pub total_commit_contributions: i64,
+    pub total_issue_contributions: i64,
+    pub total_pull_request_contributions: i64,
+    pub total_pull_request_review_contributions: i64,
+    pub restricted_contributions_count: i64,
+    pub contribution_calendar: GqlCalendar,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GqlCalendar {
+    pub total_contributions: i64,
+    pub weeks: Vec<GqlWeek>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GqlWeek {
+    pub contribution_days: Vec<ContributionDay>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all(deserialize = "camelCase"))]
+pub struct GqlPinnedRepo {
+    pub name: String,
+    pub name_with_owner: String,
+    pub description: Option<String>,
+    pub url: String,
+    pub stargazer_count: i64,
+    pub fork_count: i64,
+    pub primary_language: Option<GqlLanguage>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GqlLanguage {
+    pub name: String,
+    pub color: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GqlRepositories {
+    pub total_count: i64,
+    pub page_info: GqlPageInfo,
+    pub nodes: Vec<GqlRepoLanguages>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GqlPageInfo {
+    pub has_next_page: bool,
+    pub end_cursor: Option<String>,
+}
+
+// GraphQL `user` payload for a further page of profile repositories
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProfileReposData {
+    pub user: Option<GqlUserRepos>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GqlUserRepos {
+    pub repositories: GqlRepositories,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GqlRepoLanguages {
+    pub stargazer_count: i64,
+    pub languages: GqlLanguageEdges,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GqlLanguageEdges {
+    pub edges: Vec<GqlLanguageEdge>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GqlLanguageEdge {
+    pub size: i64,
+    pub node: GqlLanguage,
+}
+
+// One square of the contribution calendar
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContributionDay {
+    pub date: String,
+    #[serde(alias = "contributionCount")]
+    pub count: i64,
+    pub color: String,
+    pub weekday: i64,
+    // Intensity 0-4, from GitHub's `contributionLevel` quartiles
+    #[serde(default, alias = "contributionLevel", deserialize_with = "contribution_level")]
+    pub level: u8,
+}
+
+fn contribution_level<'de, D>(deserializer: D) -> Result<u8, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(match Value::deserialize(deserializer)? {
+        Value::Number(level) => level.as_u64().unwrap_or(0).min(4) as u8,
+        Value::String(level) => match level.as_str() {
+            "FIRST_QUARTILE" => 1,
+            "SECOND_QUARTILE" => 2,
+            "THIRD_QUARTILE" => 3,
+            "FOURTH_QUARTILE" => 4,
+            _ => 0,
+        },
+        _ => 0,
+    })
+}
+
+// GraphQL payload for the contributions query
+#[derive(Debug, Clone, Deserialize)]
+pub struct ContributionsData {
+    pub user: Option<GqlContributionsUser>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GqlContributionsUser {
+    pub login: String,
+    pub contributions_collection: GqlCalendarCollection,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GqlCalendarCollection {
+    pub started_at: String,
+    pub ended_at: String,
+    pub contribution_calendar: GqlCalendar,
+}
+
+// Calendar served by `/v1/github/contributions/{login}`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContributionCalendar {
+    pub login: String,
+    pub from: String,
+    pub to: String,
+    pub total: i64,
+    pub days: Vec<ContributionDay>,
+}
+
+impl From<GqlContributionsUser> for ContributionCalendar {
+    fn from(user: GqlContributionsUser) -> Self {
+        let collection = user.contributions_collection;
+        Self {
+            login: user.login,
+            from: collection.started_at,
+            to: collection.ended_at,
+            total: collection.contribution_calendar.total_contributions,
+            days: collection
+                .contribution_calendar
+                .weeks
+                .into_iter()
+                .flat_map(|week| week.contribution_days)
+                .collect(),
+        }
+    }
+}
+
+// Profile served by `/v1/github/profile/{login}`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GithubProfile {
+    pub login: String,
+    pub name: Option<String>,
+    pub bio: Option<String>,
+    pub avatar_url: String,
+    pub url: String,
+    pub company: Option<String>,
+    pub location: Option<String>,
+    pub website_url: Option<String>,
+    pub created_at: String,
+    pub followers: i64,
+    pub following: i64,
+    pub repositories: i64,
+    pub total_stars: i64,
+    pub open_pull_requests: i64,
+    pub open_issues: i64,
+    pub contributions: ContributionSummary,
+    pub pinned: Vec<GqlPinnedRepo>,
+    pub languages: Vec<LanguageBytes>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContributionSummary {
+    pub total: i64,
+    pub commits: i64,
+    pub issues: i64,
+    pub pull_requests: i64,
+    pub reviews: i64,
+    pub restricted: i64,
+    pub calendar: Vec<ContributionDay>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LanguageBytes {
+    pub name: String,
+    pub color: Option<String>,
+    pub bytes: i64,
+    pub percent: f64,
+}
+
+impl From<GqlUser> for GithubProfile {
+    fn from(user: GqlUser) -> Self {
+        let mut languages: Vec<LanguageBytes> = Vec::new();
+        for edge in user.repositories.nodes.iter().flat_map(|repo| &repo.languages.edges) {
+            match languages.iter_mut().find(|l| l.name == edge.node.name) {
+                Some(language) => language.bytes += edge.size,
+                None => languages.push(LanguageBytes {
+                    name: edge.node.name.clone(),
+                    color: edge.node.color.clone(),
+                    bytes: edge.size,
+                    percent: 0.0,
+                }),
+            }
+        }
+        let total_bytes: i64 = languages.iter().map(|l| l.bytes).sum();
+        for language in &mut languages {
+            language.percent = if total_bytes > 0 {
+                (language.bytes as f64 * 10000.0 / total_bytes as f64).round() / 100.0
+            } else {
+                0.0
+            };
+        }
+        languages.sort_by_key(|l| std::cmp::Reverse(l.bytes));
+
+        let collection = user.contributions_collection;
+        Self {
+            login: user.login,
+            name: user.name,
+            bio: user.bio,
+            avatar_url: user.avatar_url,
+            url: user.url,
+            company: user.company,
+            location: user.location,
+            website_url: user.website_url,
+            created_at: user.created_at,
+            followers: user.followers.total_count,
+            following: user.following.total_count,
+            repositories: user.repositories.total_count,
+            total_stars: user.repositories.nodes.iter().map(|repo| repo.stargazer_count).sum(),
+            open_pull_requests: user.open_pull_requests.total_count,
+            open_issues: user.open_issues.total_count,
+            contributions: ContributionSummary {
+                total: collection.contribution_calendar.total_contributions,
+                commits: collection.total_commit_contributions,
+                issues: collection.total_issue_contributions,
+                pull_requests: collection.total_pull_request_contributions,
+                reviews: collection.total_pull_request_review_contributions,
+                restricted: collection.restricted_contributions_count,
+                calendar: collection
+                    .contribution_calendar
+                    .weeks
+                    .into_iter()
+                    .flat_map(|week| week.contribution_days)
+                    .collect(),
+            },
+            pinned: user.pinned_items.nodes,
+            languages,
+        }
+    }
+}
+
+// GitHub Actions workflow runs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowRunsResponse {
+    pub total_count: i64,
+    pub workflow_runs: Vec<WorkflowRun>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowRun {
+    pub id: i64,
+    pub name: Option<String>,
+    #[serde(default)]
+    pub display_title: Option<String>,
+    pub head_branch: Option<String>,
+    pub head_sha: String,
+    pub event: String,
+    pub status: Option<String>,
+    pub conclusion: Option<String>,
+    pub workflow_id: i64,
+    pub run_number: i64,
+    #[serde(default)]
+    pub run_attempt: Option<i64>,
+    pub html_url: String,
+    pub created_at: String,
+    pub updated_at: String,
+    #[serde(default)]
+    pub run_started_at: Option<String>,
+    pub actor: Option<RunActor>,
+    pub repository: RunRepository,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunActor {
+    pub login: String,
+}
+
+// Entry of GitHub's public events API, used when no webhook is feeding `github_events`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublicEvent {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub actor: RunActor,
+    pub repo: PublicEventRepo,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublicEventRepo {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunRepository {
+    pub full_name: String,
+}
+
+// Filters passed through to GitHub's list-runs endpoint
+#[derive(Debug, Clone, Default)]
+pub struct RunFilter {
+    pub branch: Option<String>,
+    pub status: Option<String>,
+    pub per_page: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowJobsResponse {
+    pub total_count: i64,
+    pub jobs: Vec<WorkflowJob>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowJob {
+    pub id: i64,
+    pub run_id: i64,
+    pub name: String,
+    pub status: String,
+    pub conclusion: Option<String>,
+    pub started_at: Option<String>,
+    pub completed_at: Option<String>,
+    pub html_url: Option<String>,
+    pub runner_name: Option<String>,
+    #[serde(default)]
+    pub labels: Vec<String>,
+    #[serde(default)]
+    pub steps: Vec<JobStep>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobStep {
+    pub number: i64,
+    pub name: String,
+    pub status: String,
+    pub conclusion: Option<String>,
+    pub started_at: Option<String>,
+    pub completed_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BuildState {
+    Passing,
+    Failing,
+    Running,
+    // Cancelled, skipped or never run
+    Unknown,
+}
+
+// Latest run on a repository's default branch, for the status board
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BuildHealth {
+    pub repo: String,
+    pub branch: String,
+    pub state: BuildState,
+    pub run: Option<WorkflowRun>,
+}
+
+impl BuildHealth {
+    pub fn new(repo: String, branch: String, run: Option<WorkflowRun>) -> Self {
+        let state = match &run {
+            Some(run) if run.status.as_deref() != Some("completed") => BuildState::Running,
+            Some(run) => match run.conclusion.as_deref() {
+                Some("success") => BuildState::Passing,
+                Some("failure") | Some("timed_out") | Some("startup_failure") => BuildState::Failing,
+                _ => BuildState::Unknown,
+            },
+            None => BuildState::Unknown,
+        };
+        Self {
+            repo,
+            branch,
+            state,
+            run,
+        }
+    }
+}
+
+// A stored webhook delivery, as shown in the activity feed
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct GithubEvent {
+    pub id: i64,
+    pub delivery_id: String,
+    pub event: String,
+    pub action: Option<String>,
+    pub repo: Option<String>,
+    pub sender: Option<String>,
+    pub summary: String,
+    pub received_at: chrono::DateTime<chrono::Utc>,
+}
+
+// Last known state of a runner, as tracked by the fleet poller
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct RunnerState {
+    pub owner: String,
+    pub runner_id: i64,
+    pub name: String,
+    pub os: String,
+    pub labels: Vec<String>,
+    pub status: String,
+    pub busy: bool,
+    pub status_since: chrono::DateTime<chrono::Utc>,
+    pub last_seen_at: chrono::DateTime<chrono::Utc>,
+}
+
+// Time a runner spent online and busy within the requested window
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct RunnerUptime {
+    pub runner_id: i64,
+    pub name: String,
+    pub os: String,
+    pub labels: Vec<String>,
+    pub status: String,
+    pub busy: bool,
+    pub status_since: chrono::DateTime<chrono::Utc>,
+    pub tracked_seconds: f64,
+    pub online_seconds: f64,
+    pub busy_seconds: f64,
+    #[sqlx(skip)]
+    pub uptime_percent: f64,
+    #[sqlx(skip)]
+    pub utilization_percent: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LabelUptime {
+    pub label: String,
+    pub runners: usize,
+    pub online: usize,
+    pub tracked_seconds: f64,
+    pub online_seconds: f64,
+    pub busy_seconds: f64,
+    pub uptime_percent: f64,
+    pub utilization_percent: f64,
+}
+
+// Share of `part` in `whole` as a percentage with two decimals
+pub fn percent(part: f64, whole: f64) -> f64 {
+    if whole > 0.0 {
+        (part * 10000.0 / whole).round() / 100.0
+    } else {
+        0.0
+    }
+}
+
+// Release as returned by the REST API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GithubRelease {
+    pub id: i64,
+    pub tag_name: String,
+    pub name: Option<String>,
+    pub body: Option<String>,
+    pub draft: bool,
+    pub prerelease: bool,
+    pub html_url: String,
+    pub created_at: String,
+    pub published_at: Option<String>,
+    pub author: Option<RunActor>,
+    #[serde(default)]
+    pub assets: Vec<ReleaseAsset>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReleaseAsset {
+    pub id: i64,
+    pub name: String,
+    pub content_type: String,
+    pub size: i64,
+    pub download_count: i64,
+    pub browser_download_url: String,
+}
+
+// Release served by `/v1/github/releases`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReleaseSummary {
+    pub repo: String,
+    pub tag: String,
+    pub name: String,
+    // Normalized semantic version, when the tag is one
+    pub version: Option<String>,
+    pub prerelease: bool,
+    pub html_url: String,
+    pub published_at: String,
+    pub author: Option<String>,
+    pub body: Option<String>,
+    pub downloads: i64,
+    pub assets: Vec<ReleaseAsset>,
+}
+
+impl ReleaseSummary {
+    pub fn new(repo: &str, release: GithubRelease) -> Self {
+        Self {
+            repo: repo.to_string(),
+            name: release
+                .name
+                .filter(|name| !name.trim().is_empty())
+                .unwrap_or_else(|| release.tag_name.clone()),
+            version: parse_version(&release.tag_name).map(|version| version.to_string()),
+            tag: release.tag_name,
+            prerelease: release.prerelease,
+            html_url: release.html_url,
+            published_at: release.published_at.unwrap_or(release.created_at),
+            author: release.author.map(|author| author.login),
+            body: release.body,
+            downloads: release.assets.iter().map(|asset| asset.download_count).sum(),
+            assets: release.assets,
+        }
+    }
+}
+
+// Read a tag like `v1.2`, `1.2.3-rc.1` or `release-2.0.0` as a semantic version
+pub fn parse_version(tag: &str) -> Option<semver::Version> {
+    let start = tag.find(|c: char| c.is_ascii_digit())?;
+    let version = &tag[start..];
+    if let Ok(version) = semver::Version::parse(version) {
+        return Some(version);
+    }
+    // Pad short versions: `1.2` -> `1.2.0`, keeping any pre-release suffix
+    let (core, rest) = match version.find(['-', '+']) {
+        Some(index) => version.split_at(index),
+        None => (version, ""),
+    };
+    let parts = core.split('.').count();
+    if parts >= 3 || !core.split('.').all(|part| part.parse::<u64>().is_ok()) {
+        return None;
+    }
+    let padded = format!("{}{}{}", core, ".0".repeat(3 - parts), rest);
+    semver::Version::parse(&padded).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_version;
+
+    #[test]
+    fn parse_version_strips_prefix() {
+        assert_eq!(parse_version("v1.4.2"), Some(semver::Version::new(1, 4, 2)));
+        assert_eq!(parse_version("release-2.0.1"), Some(semver::Version::new(2, 0, 1)));
+    }
+
+    #[test]
+    fn parse_version_pads_short_versions() {
+        assert_eq!(parse_version("1.2"), Some(semver::Version::new(1, 2, 0)));
+        assert_eq!(parse_version("v3"), Some(semver::Version::new(3, 0, 0)));
+    }
+
+    #[test]
+    fn parse_version_keeps_pre_release() {
+        let version = parse_version("v1.2-beta.1").unwrap();
+        assert_eq!(version.to_string(), "1.2.0-beta.1");
+        assert!(version < semver::Version::new(1, 2, 0));
+        assert_eq!(parse_version("v2.0.0-rc.1").unwrap().pre.as_str(), "rc.1");
+    }
+
+    #[test]
+    fn parse_version_rejects_non_versions() {
+        assert_eq!(parse_version("nightly"), None);
+        assert_eq!(parse_version("1.2.x"), None);
+    }
+}
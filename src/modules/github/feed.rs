@@ -0,0 +1,56 @@
+use std::fmt::Write;
+
+use super::entity::ReleaseSummary;
+
+// Atom 1.0 document listing releases, newest entry first
+pub fn releases_atom(title: &str, self_url: &str, releases: &[ReleaseSummary]) -> String {
+    let updated = releases
+        .iter()
+        .map(|release| release.published_at.as_str())
+        .max()
+        .map(str::to_string)
+        .unwrap_or_else(|| chrono::Utc::now().to_rfc3339());
+
+    let mut xml = String::new();
+    xml.push_str(r#"<?xml version="1.0" encoding="utf-8"?>"#);
+    xml.push('\n');
+    xml.push_str(r#"<feed xmlns="http://www.w3.org/2005/Atom">"#);
+    let _ = write!(xml, "<id>{}</id>", escape(self_url));
+    let _ = write!(xml, "<title>{}</title>", escape(title));
+    let _ = write!(xml, "<updated>{}</updated>", escape(&updated));
+    let _ = write!(xml, r#"<link rel="self" href="{}"/>"#, escape(self_url));
+
+    for release in releases {
+        let mut content = release.body.clone().unwrap_or_default();
+        if !release.assets.is_empty() {
+            let _ = write!(content, "\n\n{} downloads across {} assets", release.downloads, release.assets.len());
+        }
+        xml.push_str("<entry>");
+        let _ = write!(xml, "<id>{}</id>", escape(&release.html_url));
+        let _ = write!(
+            xml,
+            "<title>{} {}{}</title>",
+            escape(&release.repo),
+            escape(&release.name),
+            if release.prerelease { " (pre-release)" } else { "" }
+        );
+        let _ = write!(xml, "<updated>{}</updated>", escape(&release.published_at));
+        let _ = write!(xml, r#"<link rel="alternate" href="{}"/>"#, escape(&release.html_url));
+        if let Some(author) = &release.author {
+            let _ = write!(xml, "<author><name>{}</name></author>", escape(author));
+        }
+        let _ = write!(xml, r#"<content type="text">{}</content>"#, escape(&content));
+        xml.push_str("</entry>");
+    }
+
+    xml.push_str("</feed>\n");
+    xml
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
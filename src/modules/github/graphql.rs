@@ -0,0 +1,145 @@
+use std::collections::HashMap;
+
+use envconfig::Envconfig;
+use gql_client::Client as GQLClient;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    config::Config,
+    error::{AppError, GithubError},
+};
+
+const ENDPOINT: &str = "https://api.github.com/graphql";
+
+// Everything `/v1/github/profile/{login}` needs, with the first 100 source repositories.
+// Stars and languages are summed over all of them, paging on with `PROFILE_REPOS_QUERY`.
+pub const PROFILE_QUERY: &str = r#"
+query($login: String!) {
+  user(login: $login) {
+    login
+    name
+    bio
+    avatarUrl
+    url
+    company
+    location
+    websiteUrl
+    createdAt
+    followers { totalCount }
+    following { totalCount }
+    contributionsCollection {
+      totalCommitContributions
+      totalIssueContributions
+      totalPullRequestContributions
+      totalPullRequestReviewContributions
+      restrictedContributionsCount
+      contributionCalendar {
+        totalContributions
+        weeks { contributionDays { date contributionCount color weekday contributionLevel } }
+      }
+    }
+    pinnedItems(first: 6, types: REPOSITORY) {
+      nodes {
+        ... on Repository {
+          name
+          nameWithOwner
+          description
+          url
+          stargazerCount
+          forkCount
+          primaryLanguage { name color }
+        }
+      }
+    }
+    repositories(first: 100, ownerAffiliations: OWNER, isFork: false, orderBy: {field: STARGAZERS, direction: DESC}) {
+      totalCount
+      pageInfo { hasNextPage endCursor }
+      nodes {
+        stargazerCount
+        languages(first: 20, orderBy: {field: SIZE, direction: DESC}) {
+          edges { size node { name color } }
+        }
+      }
+    }
+    openPullRequests: pullRequests(states: OPEN) { totalCount }
+    openIssues: issues(states: OPEN) { totalCount }
+  }
+}
+"#;
+
+// Further pages of the profile's source repositories, after `endCursor`
+pub const PROFILE_REPOS_QUERY: &str = r#"
+query($login: String!, $after: String!) {
+  user(login: $login) {
+    repositories(first: 100, after: $after, ownerAffiliations: OWNER, isFork: false, orderBy: {field: STARGAZERS, direction: DESC}) {
+      totalCount
+      pageInfo { hasNextPage endCursor }
+      nodes {
+        stargazerCount
+        languages(first: 20, orderBy: {field: SIZE, direction: DESC}) {
+          edges { size node { name color } }
+        }
+      }
+    }
+  }
+}
+"#;
+
+// Contribution calendar for a range of at most one year; GitHub defaults to the past year
+pub const CONTRIBUTIONS_QUERY: &str = r#"
+query($login: String!, $from: DateTime, $to: DateTime) {
+  user(login: $login) {
+    login
+    contributionsCollection(from: $from, to: $to) {
+      startedAt
+      endedAt
+      contributionCalendar {
+        totalContributions
+        weeks { contributionDays { date contributionCount color weekday contributionLevel } }
+      }
+    }
+  }
+}
+"#;
+
+// GitHub GraphQL API access through `gql_client`
+#[derive(Clone)]
+pub struct GithubGraphql;
+
+impl GithubGraphql {
+    pub async fn query<K, V>(query: &str, variables: V) -> Result<K, AppError>
+    where
+        K: DeserializeOwned,
+        V: Serialize,
+    {
+        let config = Config::init_from_env()?;
+        let mut headers = HashMap::new();
+        headers.insert("Authorization", format!("Bearer {}", config.github_secret));
+        headers.insert("User-Agent", "NullDev-API".to_string());
+
+        let client = GQLClient::new_with_headers(ENDPOINT, headers);
+        let data: Option<K> = client
+            .query_with_vars(query, variables)
+            .await
+            .map_err(|e| map_error(&e.to_string()))?;
+        data.ok_or_else(|| GithubError::Schema("GraphQL response had no data".to_string()).into())
+    }
+}
+
+// `gql_client` only exposes errors as text, so classify them by their message
+fn map_error(message: &str) -> AppError {
+    let message = message.trim().replace("GQLClient Error: ", "");
+    let lower = message.to_lowercase();
+    let error = if lower.contains("could not resolve to a user") {
+        GithubError::NotFound(message)
+    } else if lower.contains("rate limit") {
+        GithubError::RateLimited { reset: None }
+    } else if lower.contains("[401]") || lower.contains("[403]") {
+        GithubError::Unauthorized(message)
+    } else if lower.contains("failed to parse response") {
+        GithubError::Schema(message)
+    } else {
+        GithubError::Upstream(message)
+    };
+    error.into()
+}
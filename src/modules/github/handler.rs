@@ -1,27 +1,587 @@
-use actix_web::{get, http::Error, web, HttpResponse};
-use serde_json::json;
+use actix_web::{get, routes, web, HttpRequest, HttpResponse};
+use chrono::{DateTime, Duration, Utc};
+use envconfig::Envconfig;
+use log::{error, info};
+use serde::Deserialize;
+use serde_json::{json, Value};
 use tokio::sync::Mutex;
 
-use crate::{client::NullClient, modules::github::GithubManager};
+use crate::{
+    client::NullClient,
+    config::Config,
+    error::AppError,
+    modules::github::{
+        entity::{
+            parse_version, percent, BuildState, CacheInfo, CacheStatus, GithubOwner, LabelUptime,
+            ReleaseSummary, RepoSummary, Root2, RunFilter, WorkflowRun,
+        },
+        feed,
+        heatmap::{self, HeatmapTheme},
+        webhook::{verify_signature, WebhookEvent},
+        GithubManager, GithubRepository,
+    },
+};
 
+#[routes]
 #[get("/v1/github/runners")]
-async fn runners(data: web::Data<Mutex<NullClient>>) -> Result<HttpResponse, Error> {
+#[get("/v1/github/{owner}/runners")]
+async fn runners(
+    path: web::Path<OwnerPath>,
+    data: web::Data<Mutex<NullClient>>,
+) -> Result<HttpResponse, AppError> {
+    let owner = GithubManager::resolve_owner(path.owner.as_deref())?;
     let data = data.lock().await;
     let redis = &mut data.redis.clone();
-    let runners = GithubManager::get_runners(redis).await.unwrap();
-    // TODO Add Caching
+    let pool = data.postgres.pool.clone();
+    drop(data); // Release the lock early
+    let runners = GithubManager::get_runners(redis, &owner).await?;
+    // Only snapshot data GitHub has just confirmed
+    if runners.cache.status != CacheStatus::Hit {
+        if let Err(e) = GithubRepository::record_runners(&pool, &owner.login, &runners.data).await {
+            error!("Failed to record runner snapshot: {}", e);
+        }
+    }
     Ok(HttpResponse::Ok()
         .insert_header(("Content-Type", "application/json"))
-        .body(json!({"success": true, "data": runners}).to_string()))
+        .body(json!({"success": true, "owner": owner, "data": runners.data, "cache": runners.cache}).to_string()))
 }
 
+// Uptime and utilization per runner and per label, from the fleet poller's history
+#[routes]
+#[get("/v1/github/runners/fleet")]
+#[get("/v1/github/{owner}/runners/fleet")]
+async fn runner_fleet(
+    path: web::Path<OwnerPath>,
+    query: web::Query<FleetQuery>,
+    data: web::Data<Mutex<NullClient>>,
+) -> Result<HttpResponse, AppError> {
+    let owner = GithubManager::resolve_owner(path.owner.as_deref())?;
+    let hours = query.hours.clamp(1, 24 * 90);
+    let since = Utc::now() - Duration::hours(hours);
+    let pool = data.lock().await.postgres.pool.clone();
+    let fleet = GithubRepository::runner_uptime(&pool, &owner.login, since).await?;
+
+    let mut labels: Vec<LabelUptime> = Vec::new();
+    for runner in &fleet {
+        for label in &runner.labels {
+            let index = match labels.iter().position(|l| &l.label == label) {
+                Some(index) => index,
+                None => {
+                    labels.push(LabelUptime {
+                        label: label.clone(),
+                        runners: 0,
+                        online: 0,
+                        tracked_seconds: 0.0,
+                        online_seconds: 0.0,
+                        busy_seconds: 0.0,
+                        uptime_percent: 0.0,
+                        utilization_percent: 0.0,
+                    });
+                    labels.len() - 1
+                }
+            };
+            let entry = &mut labels[index];
+            entry.runners += 1;
+            entry.online += usize::from(runner.status == "online");
+            entry.tracked_seconds += runner.tracked_seconds;
+            entry.online_seconds += runner.online_seconds;
+            entry.busy_seconds += runner.busy_seconds;
+        }
+    }
+    for label in &mut labels {
+        label.uptime_percent = percent(label.online_seconds, label.tracked_seconds);
+        label.utilization_percent = percent(label.busy_seconds, label.online_seconds);
+    }
+    labels.sort_by(|a, b| a.label.cmp(&b.label));
+
+    Ok(HttpResponse::Ok()
+        .insert_header(("Content-Type", "application/json"))
+        .body(
+            json!({
+                "success": true,
+                "owner": owner,
+                "since": since,
+                "data": { "runners": fleet, "labels": labels }
+            })
+            .to_string(),
+        ))
+}
+
+#[routes]
 #[get("/v1/github/repos")]
-async fn repos(data: web::Data<Mutex<NullClient>>) -> Result<HttpResponse, Error> {
+#[get("/v1/github/{owner}/repos")]
+async fn repos(
+    path: web::Path<OwnerPath>,
+    query: web::Query<RepoQuery>,
+    data: web::Data<Mutex<NullClient>>,
+) -> Result<HttpResponse, AppError> {
+    let owner = GithubManager::resolve_owner(path.owner.as_deref())?;
+    let limit = query.limit.clamp(1, 100);
+    let data = data.lock().await;
+    let redis = &mut data.redis.clone();
+    let pool = data.postgres.pool.clone();
+    drop(data); // Release the lock early
+    let repos = GithubManager::get_repos(redis, &owner).await?;
+    if repos.cache.status != CacheStatus::Hit {
+        if let Err(e) = GithubRepository::record_repos(&pool, &repos.data).await {
+            error!("Failed to record repo snapshot: {}", e);
+        }
+    }
+
+    let filtered = filter_repos(&repos.data, &query)?;
+    let total = filtered.len();
+    let page: Vec<RepoSummary> = filtered
+        .into_iter()
+        .skip(query.offset)
+        .take(limit)
+        .map(RepoSummary::from)
+        .collect();
+    Ok(HttpResponse::Ok()
+        .insert_header(("Content-Type", "application/json"))
+        .body(
+            json!({
+                "success": true,
+                "owner": owner,
+                "data": page,
+                "total": total,
+                "limit": limit,
+                "offset": query.offset,
+                "cache": repos.cache
+            })
+            .to_string(),
+        ))
+}
+
+#[get("/v1/github/profile/{login}")]
+async fn profile(
+    path: web::Path<String>,
+    data: web::Data<Mutex<NullClient>>,
+) -> Result<HttpResponse, AppError> {
+    let owner = GithubManager::resolve_user(&path)?;
+    let data = data.lock().await;
+    let redis = &mut data.redis.clone();
+    drop(data); // Release the lock early
+    let profile = GithubManager::get_profile(redis, &owner).await?;
+    Ok(HttpResponse::Ok()
+        .insert_header(("Content-Type", "application/json"))
+        .body(json!({"success": true, "data": profile.data, "cache": profile.cache}).to_string()))
+}
+
+// Registered before `contributions` so the `.svg` suffix isn't taken as part of the login
+#[get("/v1/github/contributions/{login}.svg")]
+async fn contributions_svg(
+    path: web::Path<String>,
+    query: web::Query<ContributionsQuery>,
+    data: web::Data<Mutex<NullClient>>,
+) -> Result<HttpResponse, AppError> {
+    let owner = GithubManager::resolve_user(&path)?;
+    let theme = HeatmapTheme::build(
+        query.theme.as_deref(),
+        query.bg.as_deref(),
+        query.text.as_deref(),
+        query.colors.as_deref(),
+        query.radius,
+    )?;
+    let (from, to) = query.range()?;
+    let data = data.lock().await;
+    let redis = &mut data.redis.clone();
+    drop(data); // Release the lock early
+    let calendar = GithubManager::get_contributions(redis, &owner, from, to).await?;
+    // Let browsers and image proxies keep the SVG until our cached calendar expires
+    let max_age = (Config::init_from_env()?.github_contributions_ttl - calendar.cache.age).max(0);
+    Ok(HttpResponse::Ok()
+        .insert_header(("Content-Type", "image/svg+xml"))
+        .insert_header(("Cache-Control", format!("public, max-age={}", max_age)))
+        .body(heatmap::render(&calendar.data, &theme)))
+}
+
+#[get("/v1/github/contributions/{login}")]
+async fn contributions(
+    path: web::Path<String>,
+    query: web::Query<ContributionsQuery>,
+    data: web::Data<Mutex<NullClient>>,
+) -> Result<HttpResponse, AppError> {
+    let owner = GithubManager::resolve_user(&path)?;
+    let (from, to) = query.range()?;
+    let data = data.lock().await;
+    let redis = &mut data.redis.clone();
+    drop(data); // Release the lock early
+    let calendar = GithubManager::get_contributions(redis, &owner, from, to).await?;
+    Ok(HttpResponse::Ok()
+        .insert_header(("Content-Type", "application/json"))
+        .body(json!({"success": true, "data": calendar.data, "cache": calendar.cache}).to_string()))
+}
+
+#[get("/v1/github/actions/runs")]
+async fn action_runs(
+    query: web::Query<RunsQuery>,
+    data: web::Data<Mutex<NullClient>>,
+) -> Result<HttpResponse, AppError> {
+    let limit = query.limit.clamp(1, 100);
+    let filter = RunFilter {
+        branch: query.branch.clone(),
+        // GitHub's `status` filter also accepts conclusions, which narrows the fetch
+        status: query.status.clone().or_else(|| query.conclusion.clone()),
+        per_page: limit as u32,
+    };
+    let data = data.lock().await;
+    let redis = &mut data.redis.clone();
+    drop(data); // Release the lock early
+
+    let (owner, runs, cache) = match &query.repo {
+        Some(repo) => {
+            let (owner, repo) = GithubManager::resolve_repo(query.owner.as_deref(), repo)?;
+            let runs = GithubManager::get_runs(redis, &repo, &filter).await?;
+            (owner, runs.data.workflow_runs, runs.cache)
+        }
+        None => {
+            let owner = GithubManager::resolve_owner(query.owner.as_deref())?;
+            let runs = GithubManager::get_owner_runs(redis, &owner, &filter).await?;
+            (owner, runs.data, runs.cache)
+        }
+    };
+
+    let runs: Vec<WorkflowRun> = runs
+        .into_iter()
+        .filter(|run| {
+            query
+                .conclusion
+                .as_deref()
+                .is_none_or(|conclusion| run.conclusion.as_deref() == Some(conclusion))
+        })
+        .take(limit)
+        .collect();
+    Ok(HttpResponse::Ok()
+        .insert_header(("Content-Type", "application/json"))
+        .body(json!({"success": true, "owner": owner, "data": runs, "cache": cache}).to_string()))
+}
+
+#[get("/v1/github/actions/runs/{id}/jobs")]
+async fn action_jobs(
+    path: web::Path<i64>,
+    query: web::Query<JobsQuery>,
+    data: web::Data<Mutex<NullClient>>,
+) -> Result<HttpResponse, AppError> {
+    let (_, repo) = GithubManager::resolve_repo(query.owner.as_deref(), &query.repo)?;
+    let data = data.lock().await;
+    let redis = &mut data.redis.clone();
+    drop(data); // Release the lock early
+    let jobs = GithubManager::get_jobs(redis, &repo, path.into_inner()).await?;
+    Ok(HttpResponse::Ok()
+        .insert_header(("Content-Type", "application/json"))
+        .body(json!({"success": true, "repo": repo, "data": jobs.data.jobs, "cache": jobs.cache}).to_string()))
+}
+
+#[get("/v1/github/actions/health")]
+async fn action_health(
+    query: web::Query<OwnerQuery>,
+    data: web::Data<Mutex<NullClient>>,
+) -> Result<HttpResponse, AppError> {
+    let owner = GithubManager::resolve_owner(query.owner.as_deref())?;
+    let data = data.lock().await;
+    let redis = &mut data.redis.clone();
+    drop(data); // Release the lock early
+    let health = GithubManager::get_build_health(redis, &owner).await?;
+    let count = |state| health.data.iter().filter(|h| h.state == state).count();
+    let summary = json!({
+        "passing": count(BuildState::Passing),
+        "failing": count(BuildState::Failing),
+        "running": count(BuildState::Running),
+        "unknown": count(BuildState::Unknown),
+    });
+    Ok(HttpResponse::Ok()
+        .insert_header(("Content-Type", "application/json"))
+        .body(json!({"success": true, "owner": owner, "summary": summary, "data": health.data, "cache": health.cache}).to_string()))
+}
+
+#[get("/v1/github/releases")]
+async fn releases(
+    query: web::Query<ReleasesQuery>,
+    data: web::Data<Mutex<NullClient>>,
+) -> Result<HttpResponse, AppError> {
+    let (owner, listed, cache) = load_releases(&query, &data).await?;
+    Ok(HttpResponse::Ok()
+        .insert_header(("Content-Type", "application/json"))
+        .body(json!({"success": true, "owner": owner, "data": listed, "cache": cache}).to_string()))
+}
+
+#[get("/v1/github/releases.atom")]
+async fn releases_atom(
+    req: HttpRequest,
+    query: web::Query<ReleasesQuery>,
+    data: web::Data<Mutex<NullClient>>,
+) -> Result<HttpResponse, AppError> {
+    let (owner, listed, _) = load_releases(&query, &data).await?;
+    let self_url = req.full_url().to_string();
+    let title = format!("{} releases", owner.login);
+    Ok(HttpResponse::Ok()
+        .insert_header(("Content-Type", "application/atom+xml; charset=utf-8"))
+        .body(feed::releases_atom(&title, &self_url, &listed)))
+}
+
+// Shared by the JSON and Atom release endpoints
+async fn load_releases(
+    query: &ReleasesQuery,
+    data: &web::Data<Mutex<NullClient>>,
+) -> Result<(GithubOwner, Vec<ReleaseSummary>, CacheInfo), AppError> {
+    let owner = GithubManager::resolve_owner(query.owner.as_deref())?;
+    let limit = query.limit.clamp(1, 100);
+    let mut redis = data.lock().await.redis.clone();
+    let cached = GithubManager::get_releases(&mut redis, &owner).await?;
+
+    let mut filtered: Vec<ReleaseSummary> = cached
+        .data
+        .into_iter()
+        .filter(|release| {
+            query
+                .repo
+                .as_deref()
+                .is_none_or(|repo| release.repo.eq_ignore_ascii_case(repo) || release.repo.rsplit('/').next() == Some(repo))
+        })
+        .filter(|release| query.prerelease.is_none_or(|prerelease| release.prerelease == prerelease))
+        .collect();
+    match query.sort.as_deref() {
+        None | Some("published") => {}
+        // Highest version first; tags that aren't versions go last, newest first
+        Some("version") => filtered.sort_by(|a, b| {
+            let a_version = parse_version(&a.tag);
+            let b_version = parse_version(&b.tag);
+            b_version
+                .cmp(&a_version)
+                .then_with(|| b.published_at.cmp(&a.published_at))
+        }),
+        Some(other) => {
+            return Err(AppError::BadRequest(format!(
+                "Unknown sort '{}', expected published or version",
+                other
+            )))
+        }
+    }
+    filtered.truncate(limit);
+    Ok((owner, filtered, cached.cache))
+}
+
+// Mounted by the provider, which scopes a larger body limit to this route
+pub async fn webhook(
+    req: HttpRequest,
+    body: web::Bytes,
+    data: web::Data<Mutex<NullClient>>,
+) -> Result<HttpResponse, AppError> {
+    let config = Config::init_from_env()?;
+    let header = |name: &str| req.headers().get(name).and_then(|value| value.to_str().ok());
+    verify_signature(
+        &config.github_webhook_secret,
+        header("X-Hub-Signature-256"),
+        &body,
+    )?;
+
+    let event = header("X-GitHub-Event")
+        .ok_or_else(|| AppError::BadRequest("Missing X-GitHub-Event header".to_string()))?
+        .to_string();
+    if event == "ping" {
+        return Ok(HttpResponse::Ok().json(json!({ "success": true, "data": "pong" })));
+    }
+    let delivery = header("X-GitHub-Delivery")
+        .ok_or_else(|| AppError::BadRequest("Missing X-GitHub-Delivery header".to_string()))?
+        .to_string();
+    let payload: Value = serde_json::from_slice(&body)
+        .map_err(|e| AppError::BadRequest(format!("Invalid webhook payload: {}", e)))?;
+
     let data = data.lock().await;
     let redis = &mut data.redis.clone();
-    let repos = GithubManager::get_repos(redis).await.unwrap();
-    // TODO Add Caching
+    let pool = data.postgres.pool.clone();
+    drop(data); // Release the lock early
+
+    let parsed = WebhookEvent::parse(&event, &payload);
+    let invalidated = GithubManager::invalidate(redis, &parsed.stale_cache_patterns()).await?;
+    let recorded = GithubRepository::record_event(&pool, &delivery, &parsed, &payload).await?;
+    info!(
+        "GitHub {} delivery {}: {} ({} cache entries invalidated)",
+        event, delivery, parsed.summary, invalidated
+    );
+
+    Ok(HttpResponse::Ok().json(json!({
+        "success": true,
+        "data": {
+            "event": event,
+            "summary": parsed.summary,
+            "recorded": recorded,
+            "invalidated": invalidated
+        }
+    })))
+}
+
+#[get("/v1/github/events")]
+async fn events(
+    query: web::Query<EventsQuery>,
+    data: web::Data<Mutex<NullClient>>,
+) -> Result<HttpResponse, AppError> {
+    let pool = data.lock().await.postgres.pool.clone();
+    let limit = query.limit.clamp(1, 100);
+    let events =
+        GithubRepository::events(&pool, query.event.as_deref(), query.repo.as_deref(), limit).await?;
     Ok(HttpResponse::Ok()
         .insert_header(("Content-Type", "application/json"))
-        .body(json!({"success": true, "data": repos}).to_string()))
+        .body(json!({"success": true, "data": events}).to_string()))
+}
+
+// Apply the listing filters and sort order
+fn filter_repos<'a>(all: &'a [Root2], query: &RepoQuery) -> Result<Vec<&'a Root2>, AppError> {
+    let mut filtered = Vec::new();
+    for repo in all {
+        let matches_type = match query.repo_type.as_deref().unwrap_or("all") {
+            "all" => true,
+            "public" => !repo.private,
+            "private" => repo.private,
+            "forks" => repo.fork,
+            "sources" => !repo.fork,
+            other => {
+                return Err(AppError::BadRequest(format!(
+                    "Unknown type '{}', expected all, public, private, forks or sources",
+                    other
+                )))
+            }
+        };
+        let matches_language = match &query.language {
+            Some(language) => repo
+                .language
+                .as_deref()
+                .is_some_and(|l| l.eq_ignore_ascii_case(language)),
+            None => true,
+        };
+        if matches_type
+            && matches_language
+            && query.archived.is_none_or(|archived| repo.archived == archived)
+            && query.fork.is_none_or(|fork| repo.fork == fork)
+        {
+            filtered.push(repo);
+        }
+    }
+
+    match query.sort.as_deref() {
+        None => {}
+        Some("stars") => filtered.sort_by_key(|repo| std::cmp::Reverse(repo.stargazers_count)),
+        // ISO 8601 timestamps sort lexically
+        Some("pushed") => filtered.sort_by(|a, b| b.pushed_at.cmp(&a.pushed_at)),
+        Some("name") => filtered.sort_by_key(|repo| repo.name.to_lowercase()),
+        Some(other) => {
+            return Err(AppError::BadRequest(format!(
+                "Unknown sort '{}', expected stars, pushed or name",
+                other
+            )))
+        }
+    }
+    Ok(filtered)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RepoQuery {
+    #[serde(rename = "type")]
+    pub repo_type: Option<String>,
+    pub language: Option<String>,
+    pub archived: Option<bool>,
+    pub fork: Option<bool>,
+    pub sort: Option<String>,
+    #[serde(default = "default_repo_limit")]
+    pub limit: usize,
+    #[serde(default)]
+    pub offset: usize,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RunsQuery {
+    pub owner: Option<String>,
+    pub repo: Option<String>,
+    pub branch: Option<String>,
+    pub status: Option<String>,
+    pub conclusion: Option<String>,
+    #[serde(default = "default_repo_limit")]
+    pub limit: usize,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct JobsQuery {
+    pub owner: Option<String>,
+    pub repo: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OwnerQuery {
+    pub owner: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReleasesQuery {
+    pub owner: Option<String>,
+    pub repo: Option<String>,
+    // `false` hides pre-releases, `true` shows only them
+    pub prerelease: Option<bool>,
+    pub sort: Option<String>,
+    #[serde(default = "default_repo_limit")]
+    pub limit: usize,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FleetQuery {
+    #[serde(default = "default_fleet_hours")]
+    pub hours: i64,
+}
+
+fn default_fleet_hours() -> i64 {
+    24
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EventsQuery {
+    pub event: Option<String>,
+    pub repo: Option<String>,
+    #[serde(default = "default_events_limit")]
+    pub limit: i64,
+}
+
+fn default_events_limit() -> i64 {
+    30
+}
+
+type DateRange = (Option<DateTime<Utc>>, Option<DateTime<Utc>>);
+
+#[derive(Debug, Deserialize)]
+pub struct ContributionsQuery {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    // SVG only: preset plus hex color overrides
+    pub theme: Option<String>,
+    pub bg: Option<String>,
+    pub text: Option<String>,
+    pub colors: Option<String>,
+    pub radius: Option<u32>,
+}
+
+impl ContributionsQuery {
+    // GitHub only serves calendars spanning at most a year
+    fn range(&self) -> Result<DateRange, AppError> {
+        let (from, to) = match (self.from, self.to) {
+            (Some(from), None) => (Some(from), Some(from + Duration::days(365))),
+            (None, Some(to)) => (Some(to - Duration::days(365)), Some(to)),
+            range => range,
+        };
+        if let (Some(from), Some(to)) = (from, to) {
+            if from > to || to - from > Duration::days(366) {
+                return Err(AppError::BadRequest(
+                    "from must precede to by at most one year".to_string(),
+                ));
+            }
+        }
+        Ok((from, to))
+    }
+}
+
+fn default_repo_limit() -> usize {
+    30
+}
+
+// `owner` is absent on the unscoped routes, which use the first configured owner
+#[derive(Debug, Deserialize)]
+pub struct OwnerPath {
+    pub owner: Option<String>,
 }
@@ -0,0 +1,162 @@
+use std::fmt::Write;
+
+use chrono::{Datelike, NaiveDate};
+
+use crate::error::AppError;
+
+use super::entity::ContributionCalendar;
+
+const CELL: u32 = 10;
+const GAP: u32 = 3;
+const LEFT: u32 = 32;
+const TOP: u32 = 36;
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+// Colors used to draw the heatmap; `levels` runs from no contributions to the most
+#[derive(Debug, Clone)]
+pub struct HeatmapTheme {
+    pub background: String,
+    pub text: String,
+    pub levels: [String; 5],
+    pub radius: u32,
+}
+
+impl HeatmapTheme {
+    // A named preset with optional overrides, all colors given as hex without `#`
+    pub fn build(
+        name: Option<&str>,
+        background: Option<&str>,
+        text: Option<&str>,
+        colors: Option<&str>,
+        radius: Option<u32>,
+    ) -> Result<Self, AppError> {
+        let (bg, fg, levels) = match name.unwrap_or("light") {
+            "light" => ("ffffff", "57606a", ["ebedf0", "9be9a8", "40c463", "30a14e", "216e39"]),
+            "dark" => ("0d1117", "8b949e", ["161b22", "0e4429", "006d32", "26a641", "39d353"]),
+            "halloween" => ("ffffff", "57606a", ["ebedf0", "ffee4a", "ffc501", "fe9600", "03001c"]),
+            other => {
+                return Err(AppError::BadRequest(format!(
+                    "Unknown theme '{}', expected light, dark or halloween",
+                    other
+                )))
+            }
+        };
+
+        let mut theme = Self {
+            background: hex(background.unwrap_or(bg))?,
+            text: hex(text.unwrap_or(fg))?,
+            levels: levels.map(|color| format!("#{}", color)),
+            radius: radius.unwrap_or(2).min(5),
+        };
+        if let Some(colors) = colors {
+            let colors = colors.split(',').map(hex).collect::<Result<Vec<_>, _>>()?;
+            theme.levels = colors.try_into().map_err(|_| {
+                AppError::BadRequest("colors must list exactly five hex colors".to_string())
+            })?;
+        }
+        Ok(theme)
+    }
+}
+
+// Validated `#rrggbb`-style color; anything else would end up inside the SVG markup
+fn hex(value: &str) -> Result<String, AppError> {
+    let value = value.trim().trim_start_matches('#');
+    let valid = matches!(value.len(), 3 | 4 | 6 | 8) && value.chars().all(|c| c.is_ascii_hexdigit());
+    if !valid {
+        return Err(AppError::BadRequest(format!("Invalid color '{}'", value)));
+    }
+    Ok(format!("#{}", value))
+}
+
+// Render the calendar as a GitHub-style grid: one column per week, Sunday on top
+pub fn render(calendar: &ContributionCalendar, theme: &HeatmapTheme) -> String {
+    let first = calendar
+        .days
+        .first()
+        .and_then(|day| NaiveDate::parse_from_str(&day.date, "%Y-%m-%d").ok());
+    let offset = calendar.days.first().map(|day| day.weekday as u32).unwrap_or(0);
+    let weeks = (calendar.days.len() as u32 + offset).div_ceil(7).max(1);
+    let width = LEFT + weeks * (CELL + GAP) + GAP;
+    let height = TOP + 7 * (CELL + GAP) + GAP;
+
+    let mut svg = String::new();
+    let _ = write!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}" font-family="-apple-system, BlinkMacSystemFont, Segoe UI, Helvetica, Arial, sans-serif" font-size="9">"#,
+        w = width,
+        h = height
+    );
+    let _ = write!(svg, r#"<rect width="100%" height="100%" fill="{}"/>"#, theme.background);
+    let _ = write!(
+        svg,
+        r#"<text x="{}" y="12" fill="{}" font-size="11">{} contributions by {}</text>"#,
+        LEFT,
+        theme.text,
+        calendar.total,
+        escape(&calendar.login)
+    );
+    for (row, label) in [(1, "Mon"), (3, "Wed"), (5, "Fri")] {
+        let _ = write!(
+            svg,
+            r#"<text x="0" y="{}" fill="{}">{}</text>"#,
+            TOP + row * (CELL + GAP) + CELL - 1,
+            theme.text,
+            label
+        );
+    }
+
+    let mut last_month = None;
+    for (index, day) in calendar.days.iter().enumerate() {
+        let slot = index as u32 + offset;
+        let (week, weekday) = (slot / 7, slot % 7);
+        let x = LEFT + week * (CELL + GAP);
+        let y = TOP + weekday * (CELL + GAP);
+
+        // Label a month above the first week that starts in it
+        if weekday == 0 || index == 0 {
+            let date = NaiveDate::parse_from_str(&day.date, "%Y-%m-%d").ok().or(first);
+            if let Some(date) = date {
+                if last_month != Some(date.month()) {
+                    last_month = Some(date.month());
+                    if weeks - week >= 2 {
+                        let _ = write!(
+                            svg,
+                            r#"<text x="{}" y="{}" fill="{}">{}</text>"#,
+                            x,
+                            TOP - 5,
+                            theme.text,
+                            MONTHS[date.month0() as usize]
+                        );
+                    }
+                }
+            }
+        }
+
+        let plural = if day.count == 1 { "" } else { "s" };
+        let _ = write!(
+            svg,
+            r#"<rect x="{}" y="{}" width="{c}" height="{c}" rx="{r}" ry="{r}" fill="{}"><title>{} contribution{} on {}</title></rect>"#,
+            x,
+            y,
+            theme.levels[day.level.min(4) as usize],
+            day.count,
+            plural,
+            escape(&day.date),
+            c = CELL,
+            r = theme.radius
+        );
+    }
+
+    svg.push_str("</svg>");
+    svg
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -1,45 +1,766 @@
+use std::future::Future;
+
+use chrono::{DateTime, Utc};
 use envconfig::Envconfig;
-use reqwest::header::{ACCEPT, AUTHORIZATION, USER_AGENT};
+use futures::{stream, StreamExt};
+use log::{debug, warn};
+use redis::{aio::ConnectionManager, AsyncCommands};
+use reqwest::{
+    header::{ACCEPT, AUTHORIZATION, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED, LINK, USER_AGENT},
+    Response, StatusCode, Url,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::json;
+
+use crate::{
+    config::Config,
+    db::redis::RedisManager,
+    error::{AppError, GithubError},
+};
+
+use super::{
+    entity::{
+        BuildHealth, CacheEntry, CacheInfo, CacheStatus, Cached, ContributionCalendar,
+        ContributionsData, GithubOwner, GithubProfile, GithubRelease, OwnerKind, ProfileData,
+        ProfileReposData, PublicEvent,
+        ReleaseSummary, RepoResponse, RunFilter, RunnerResponse, WorkflowJobsResponse, WorkflowRun, WorkflowRunsResponse,
+    },
+    graphql::{GithubGraphql, CONTRIBUTIONS_QUERY, PROFILE_QUERY, PROFILE_REPOS_QUERY},
+};
 
-use crate::{config::Config, db::redis::RedisManager};
+const REPOS_PER_PAGE: u32 = 100;
+const RELEASES_PER_REPO: u32 = 10;
+// Parallel requests when fanning out over an owner's repositories
+const REPO_CONCURRENCY: usize = 8;
+// Guards against a `Link` header that never ends
+const MAX_REPO_PAGES: u32 = 50;
 
-use super::entity::{RepoResponse, RunnerResponse};
+// Entries outlive their TTL so stale ones can still be revalidated with a conditional request
+const CACHE_RETENTION_SECS: i64 = 86400;
 
 #[derive(Clone)]
 pub struct GithubManager;
 
 impl GithubManager {
-    pub async fn get_runners(_redis: &mut RedisManager) -> Result<RunnerResponse, reqwest::Error> {
-        let config = Config::init_from_env().unwrap();
+    // Allow-listed owners, organizations first
+    pub fn owners() -> Result<Vec<GithubOwner>, AppError> {
+        let config = Config::init_from_env()?;
+        let parse = |list: &str, kind: OwnerKind| -> Vec<GithubOwner> {
+            list.split(',')
+                .map(str::trim)
+                .filter(|login| !login.is_empty())
+                .map(|login| GithubOwner {
+                    login: login.to_string(),
+                    kind,
+                })
+                .collect()
+        };
+        let mut owners = parse(&config.github_orgs, OwnerKind::Org);
+        owners.extend(parse(&config.github_users, OwnerKind::User));
+        Ok(owners)
+    }
 
-        let res = reqwest::Client::new()
-            .get("https://api.github.com/orgs/thenulldev/actions/runners")
-            .header(AUTHORIZATION, format!("Bearer {}", &config.github_secret))
-            .header(ACCEPT, "application/vnd.github+json")
-            .header(USER_AGENT, "NullDev-API")
-            .send()
-            .await
-            .unwrap();
-        let body = &res.text().await.unwrap();
-        // println!("{:?}", &body);
-        let body: RunnerResponse = serde_json::from_str(&body).unwrap();
-        Ok(body)
+    // Look up an owner in the allow-list, defaulting to the first configured one
+    pub fn resolve_owner(owner: Option<&str>) -> Result<GithubOwner, AppError> {
+        let owners = Self::owners()?;
+        match owner {
+            Some(owner) => owners
+                .into_iter()
+                .find(|o| o.login.eq_ignore_ascii_case(owner))
+                .ok_or_else(|| AppError::NotFound(format!("GitHub owner '{}' is not configured", owner))),
+            None => owners
+                .into_iter()
+                .next()
+                .ok_or_else(|| AppError::NotFound("No GitHub owners configured".to_string())),
+        }
+    }
+
+    // Like `resolve_owner`, but only for personal accounts
+    pub fn resolve_user(login: &str) -> Result<GithubOwner, AppError> {
+        let owner = Self::resolve_owner(Some(login))?;
+        if owner.kind != OwnerKind::User {
+            return Err(AppError::NotFound(format!("'{}' is not a configured GitHub user", login)));
+        }
+        Ok(owner)
+    }
+
+    pub async fn get_profile(
+        redis: &mut RedisManager,
+        owner: &GithubOwner,
+    ) -> Result<Cached<GithubProfile>, AppError> {
+        let config = Config::init_from_env()?;
+        let key = cache_key(&owner.login, "profile");
+        let login = owner.login.clone();
+        Self::cached_value(redis, &key, config.github_profile_ttl, || async move {
+            let data: ProfileData =
+                GithubGraphql::query(PROFILE_QUERY, json!({ "login": login })).await?;
+            let mut user = data
+                .user
+                .ok_or_else(|| GithubError::NotFound(format!("GitHub user '{}'", login)))?;
+
+            // Totals cover every source repository, not just the first page
+            let mut page_info = user.repositories.page_info.clone();
+            let mut page = 1;
+            while page_info.has_next_page {
+                let Some(after) = page_info.end_cursor.take() else {
+                    break;
+                };
+                if page >= MAX_REPO_PAGES {
+                    warn!("Stopped summing {} profile repos after {} pages", login, MAX_REPO_PAGES);
+                    break;
+                }
+                let data: ProfileReposData = GithubGraphql::query(
+                    PROFILE_REPOS_QUERY,
+                    json!({ "login": login, "after": after }),
+                )
+                .await?;
+                let repositories = data
+                    .user
+                    .ok_or_else(|| GithubError::NotFound(format!("GitHub user '{}'", login)))?
+                    .repositories;
+                user.repositories.nodes.extend(repositories.nodes);
+                page_info = repositories.page_info;
+                page += 1;
+            }
+            Ok(GithubProfile::from(user))
+        })
+        .await
+    }
+
+    pub async fn get_contributions(
+        redis: &mut RedisManager,
+        owner: &GithubOwner,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> Result<Cached<ContributionCalendar>, AppError> {
+        let config = Config::init_from_env()?;
+        let range = match (from, to) {
+            (None, None) => "contributions".to_string(),
+            _ => format!(
+                "contributions:{}:{}",
+                from.map(|from| from.timestamp()).unwrap_or_default(),
+                to.map(|to| to.timestamp()).unwrap_or_default()
+            ),
+        };
+        let key = cache_key(&owner.login, &range);
+        let login = owner.login.clone();
+        Self::cached_value(redis, &key, config.github_contributions_ttl, || async move {
+            let variables = json!({ "login": login, "from": from, "to": to });
+            let data: ContributionsData = GithubGraphql::query(CONTRIBUTIONS_QUERY, variables).await?;
+            let user = data
+                .user
+                .ok_or_else(|| GithubError::NotFound(format!("GitHub user '{}'", login)))?;
+            Ok(ContributionCalendar::from(user))
+        })
+        .await
+    }
+
+    // Split `repo` (`name` or `owner/name`) into an allow-listed owner and its full name
+    pub fn resolve_repo(owner: Option<&str>, repo: &str) -> Result<(GithubOwner, String), AppError> {
+        let (owner, name) = match repo.split_once('/') {
+            Some((owner, name)) => (Some(owner), name),
+            None => (owner, repo),
+        };
+        let valid = !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+        if !valid {
+            return Err(AppError::BadRequest(format!("Invalid repository '{}'", repo)));
+        }
+        let owner = Self::resolve_owner(owner)?;
+        let full_name = format!("{}/{}", owner.login, name);
+        Ok((owner, full_name))
+    }
+
+    pub async fn get_runs(
+        redis: &mut RedisManager,
+        repo: &str,
+        filter: &RunFilter,
+    ) -> Result<Cached<WorkflowRunsResponse>, AppError> {
+        let config = Config::init_from_env()?;
+        let mut params = vec![("per_page", filter.per_page.clamp(1, 100).to_string())];
+        if let Some(branch) = &filter.branch {
+            params.push(("branch", branch.clone()));
+        }
+        if let Some(status) = &filter.status {
+            params.push(("status", status.clone()));
+        }
+        let url = Url::parse_with_params(
+            &format!("https://api.github.com/repos/{}/actions/runs", repo),
+            &params,
+        )
+        .map_err(|e| AppError::BadRequest(e.to_string()))?;
+        let key = cache_key(repo, &format!("runs:{}", url.query().unwrap_or_default()));
+        Self::fetch_cached(redis, &key, url.as_str(), config.github_actions_ttl).await
+    }
+
+    // Recent public activity of an owner, newest first
+    pub async fn get_public_events(
+        redis: &mut RedisManager,
+        owner: &GithubOwner,
+    ) -> Result<Cached<Vec<PublicEvent>>, AppError> {
+        let config = Config::init_from_env()?;
+        let url = match owner.kind {
+            OwnerKind::Org => format!("https://api.github.com/orgs/{}/events?per_page=10", owner.login),
+            OwnerKind::User => format!(
+                "https://api.github.com/users/{}/events/public?per_page=10",
+                owner.login
+            ),
+        };
+        Self::fetch_cached(redis, &cache_key(&owner.login, "events"), &url, config.github_actions_ttl).await
+    }
+
+    // Runs across every active repository of an owner, newest first
+    pub async fn get_owner_runs(
+        redis: &mut RedisManager,
+        owner: &GithubOwner,
+        filter: &RunFilter,
+    ) -> Result<Cached<Vec<WorkflowRun>>, AppError> {
+        let repos = Self::get_repos(redis, owner).await?;
+        let names: Vec<String> = repos
+            .data
+            .iter()
+            .filter(|repo| !repo.archived)
+            .map(|repo| repo.full_name.clone())
+            .collect();
+        let results: Vec<_> = stream::iter(names)
+            .map(|name| {
+                let mut redis = redis.clone();
+                async move { Self::get_runs(&mut redis, &name, filter).await }
+            })
+            .buffer_unordered(REPO_CONCURRENCY)
+            .collect()
+            .await;
+
+        let mut runs = Vec::new();
+        let mut caches = vec![repos.cache];
+        for result in results {
+            match result {
+                Ok(cached) => {
+                    caches.push(cached.cache);
+                    runs.extend(cached.data.workflow_runs);
+                }
+                Err(e) if is_inaccessible(&e) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        runs.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        Ok(Cached {
+            data: runs,
+            next: None,
+            cache: combine_cache(caches),
+        })
+    }
+
+    pub async fn get_jobs(
+        redis: &mut RedisManager,
+        repo: &str,
+        run_id: i64,
+    ) -> Result<Cached<WorkflowJobsResponse>, AppError> {
+        let config = Config::init_from_env()?;
+        Self::fetch_cached(
+            redis,
+            &cache_key(repo, &format!("runs:{}:jobs", run_id)),
+            &format!(
+                "https://api.github.com/repos/{}/actions/runs/{}/jobs?per_page=100",
+                repo, run_id
+            ),
+            config.github_actions_ttl,
+        )
+        .await
+    }
+
+    // Latest run on each active repository's default branch
+    pub async fn get_build_health(
+        redis: &mut RedisManager,
+        owner: &GithubOwner,
+    ) -> Result<Cached<Vec<BuildHealth>>, AppError> {
+        let repos = Self::get_repos(redis, owner).await?;
+        let targets: Vec<(String, String)> = repos
+            .data
+            .iter()
+            .filter(|repo| !repo.archived)
+            .map(|repo| (repo.full_name.clone(), repo.default_branch.clone()))
+            .collect();
+        let results: Vec<_> = stream::iter(targets)
+            .map(|(name, branch)| {
+                let mut redis = redis.clone();
+                async move {
+                    let filter = RunFilter {
+                        branch: Some(branch.clone()),
+                        status: None,
+                        per_page: 1,
+                    };
+                    let runs = Self::get_runs(&mut redis, &name, &filter).await;
+                    (name, branch, runs)
+                }
+            })
+            .buffer_unordered(REPO_CONCURRENCY)
+            .collect()
+            .await;
+
+        let mut health = Vec::new();
+        let mut caches = vec![repos.cache];
+        for (name, branch, result) in results {
+            match result {
+                Ok(cached) => {
+                    caches.push(cached.cache);
+                    let run = cached.data.workflow_runs.into_iter().next();
+                    health.push(BuildHealth::new(name, branch, run));
+                }
+                Err(e) if is_inaccessible(&e) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        health.sort_by(|a, b| a.repo.cmp(&b.repo));
+        Ok(Cached {
+            data: health,
+            next: None,
+            cache: combine_cache(caches),
+        })
+    }
+
+    // Recent releases of every active repository, drafts excluded
+    pub async fn get_releases(
+        redis: &mut RedisManager,
+        owner: &GithubOwner,
+    ) -> Result<Cached<Vec<ReleaseSummary>>, AppError> {
+        let config = Config::init_from_env()?;
+        let repos = Self::get_repos(redis, owner).await?;
+        let names: Vec<String> = repos
+            .data
+            .iter()
+            .filter(|repo| !repo.archived)
+            .map(|repo| repo.full_name.clone())
+            .collect();
+        let results: Vec<_> = stream::iter(names)
+            .map(|name| {
+                let mut redis = redis.clone();
+                async move {
+                    let releases = Self::fetch_cached::<Vec<GithubRelease>>(
+                        &mut redis,
+                        &cache_key(&name, "releases"),
+                        &format!(
+                            "https://api.github.com/repos/{}/releases?per_page={}",
+                            name, RELEASES_PER_REPO
+                        ),
+                        config.github_releases_ttl,
+                    )
+                    .await;
+                    (name, releases)
+                }
+            })
+            .buffer_unordered(REPO_CONCURRENCY)
+            .collect()
+            .await;
+
+        let mut releases = Vec::new();
+        let mut caches = vec![repos.cache];
+        for (name, result) in results {
+            match result {
+                Ok(cached) => {
+                    caches.push(cached.cache);
+                    releases.extend(
+                        cached
+                            .data
+                            .into_iter()
+                            .filter(|release| !release.draft)
+                            .map(|release| ReleaseSummary::new(&name, release)),
+                    );
+                }
+                Err(e) if is_inaccessible(&e) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        releases.sort_by(|a, b| b.published_at.cmp(&a.published_at));
+        Ok(Cached {
+            data: releases,
+            next: None,
+            cache: combine_cache(caches),
+        })
     }
 
-    pub async fn get_repos(_redis: &mut RedisManager) -> Result<RepoResponse, reqwest::Error> {
-        let config = Config::init_from_env().unwrap();
+    pub async fn get_runners(
+        redis: &mut RedisManager,
+        owner: &GithubOwner,
+    ) -> Result<Cached<RunnerResponse>, AppError> {
+        let config = Config::init_from_env()?;
+        match owner.kind {
+            OwnerKind::Org => {
+                Self::fetch_cached(
+                    redis,
+                    &cache_key(&owner.login, "runners"),
+                    &format!("https://api.github.com/orgs/{}/actions/runners", owner.login),
+                    config.github_runners_ttl,
+                )
+                .await
+            }
+            // Personal accounts have no account-level runners, so gather them per repository
+            OwnerKind::User => {
+                let repos = Self::get_repos(redis, owner).await?;
+                let names: Vec<String> = repos
+                    .data
+                    .iter()
+                    .filter(|repo| repo.permissions.admin && !repo.archived)
+                    .map(|repo| repo.full_name.clone())
+                    .collect();
+                let results: Vec<_> = stream::iter(names)
+                    .map(|name| {
+                        let mut redis = redis.clone();
+                        async move {
+                            let result = Self::fetch_cached::<RunnerResponse>(
+                                &mut redis,
+                                &cache_key(&name, "runners"),
+                                &format!("https://api.github.com/repos/{}/actions/runners", name),
+                                config.github_runners_ttl,
+                            )
+                            .await;
+                            (name, result)
+                        }
+                    })
+                    .buffer_unordered(REPO_CONCURRENCY)
+                    .collect()
+                    .await;
+
+                // One unreadable repository shouldn't hide the rest of the fleet
+                let mut runners = Vec::new();
+                let mut caches = vec![repos.cache];
+                for (name, result) in results {
+                    match result {
+                        Ok(cached) => {
+                            caches.push(cached.cache);
+                            runners.extend(cached.data.runners);
+                        }
+                        Err(e) if is_inaccessible(&e) => {
+                            debug!("Skipping runners for {}", name);
+                        }
+                        Err(e) => warn!("Failed to fetch runners for {}: {}", name, e),
+                    }
+                }
+                runners.sort_by_key(|runner| runner.id);
+                runners.dedup_by_key(|runner| runner.id);
+                Ok(Cached {
+                    data: RunnerResponse {
+                        total_count: runners.len() as i64,
+                        runners,
+                    },
+                    next: None,
+                    cache: combine_cache(caches),
+                })
+            }
+        }
+    }
+
+    pub async fn get_repos(
+        redis: &mut RedisManager,
+        owner: &GithubOwner,
+    ) -> Result<Cached<RepoResponse>, AppError> {
+        let config = Config::init_from_env()?;
+        let kind = match owner.kind {
+            OwnerKind::Org => "orgs",
+            OwnerKind::User => "users",
+        };
+
+        // Each page is cached and revalidated on its own, following the `Link` header
+        let mut url = Some(format!(
+            "https://api.github.com/{}/{}/repos?per_page={}",
+            kind, owner.login, REPOS_PER_PAGE
+        ));
+        let mut repos = Vec::new();
+        let mut caches = Vec::new();
+        let mut page = 1;
+        while let Some(page_url) = url {
+            if page > MAX_REPO_PAGES {
+                warn!("Stopped listing {} repos after {} pages", owner.login, MAX_REPO_PAGES);
+                break;
+            }
+            let cached: Cached<RepoResponse> = Self::fetch_cached(
+                redis,
+                &cache_key(&owner.login, &format!("repos:{}", page)),
+                &page_url,
+                config.github_repos_ttl,
+            )
+            .await?;
+            repos.extend(cached.data);
+            caches.push(cached.cache);
+            url = cached.next;
+            page += 1;
+        }
 
-        let res = reqwest::Client::new()
-            .get("https://api.github.com/orgs/thenulldev/repos")
+        Ok(Cached {
+            data: repos,
+            next: None,
+            cache: combine_cache(caches),
+        })
+    }
+
+    // Drop cached responses matching any of the patterns; returns how many were removed
+    pub async fn invalidate(redis: &mut RedisManager, patterns: &[String]) -> Result<u64, AppError> {
+        let mut removed = 0;
+        for pattern in patterns {
+            let mut cursor: u64 = 0;
+            loop {
+                let (next, keys): (u64, Vec<String>) = redis::cmd("SCAN")
+                    .arg(cursor)
+                    .arg("MATCH")
+                    .arg(pattern)
+                    .arg("COUNT")
+                    .arg(100)
+                    .query_async(&mut redis.connection)
+                    .await?;
+                if !keys.is_empty() {
+                    removed += redis.connection.del::<_, u64>(&keys).await?;
+                }
+                if next == 0 {
+                    break;
+                }
+                cursor = next;
+            }
+        }
+        Ok(removed)
+    }
+
+    // Cache a computed value for `ttl` seconds; for sources without validators like GraphQL
+    async fn cached_value<T, F, Fut>(
+        redis: &mut RedisManager,
+        key: &str,
+        ttl: i64,
+        fetch: F,
+    ) -> Result<Cached<T>, AppError>
+    where
+        T: Clone + Serialize + DeserializeOwned,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, AppError>>,
+    {
+        let now = chrono::Utc::now().timestamp();
+        let cached: Option<String> = redis.connection.get(key).await?;
+        let cached: Option<CacheEntry<T>> = cached.and_then(|entry| serde_json::from_str(&entry).ok());
+        if let Some(entry) = &cached {
+            if now - entry.fetched_at < ttl {
+                return Ok(Self::cached(entry, CacheStatus::Hit, now));
+            }
+        }
+
+        let entry = CacheEntry {
+            etag: None,
+            last_modified: None,
+            next: None,
+            fetched_at: now,
+            body: fetch().await?,
+        };
+        redis::cmd("SET")
+            .arg(key)
+            .arg(serde_json::to_string(&entry)?)
+            .arg("EX")
+            .arg(ttl.max(1))
+            .query_async::<ConnectionManager, String>(&mut redis.connection)
+            .await?;
+        Ok(Self::cached(&entry, CacheStatus::Miss, now))
+    }
+
+    // Serve from Redis while fresh, otherwise revalidate with ETag/Last-Modified
+    async fn fetch_cached<T>(
+        redis: &mut RedisManager,
+        key: &str,
+        url: &str,
+        ttl: i64,
+    ) -> Result<Cached<T>, AppError>
+    where
+        T: Clone + Serialize + DeserializeOwned,
+    {
+        let config = Config::init_from_env()?;
+        let now = chrono::Utc::now().timestamp();
+
+        let cached: Option<String> = redis.connection.get(key).await?;
+        let cached: Option<CacheEntry<T>> = cached.and_then(|entry| serde_json::from_str(&entry).ok());
+        if let Some(entry) = &cached {
+            if now - entry.fetched_at < ttl {
+                return Ok(Self::cached(entry, CacheStatus::Hit, now));
+            }
+        }
+
+        let mut request = reqwest::Client::new()
+            .get(url)
             .header(AUTHORIZATION, format!("Bearer {}", &config.github_secret))
             .header(ACCEPT, "application/vnd.github+json")
-            .header(USER_AGENT, "NullDev-API")
+            .header(USER_AGENT, "NullDev-API");
+        if let Some(entry) = &cached {
+            if let Some(etag) = &entry.etag {
+                request = request.header(IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &entry.last_modified {
+                request = request.header(IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+        let res = request
             .send()
             .await
-            .unwrap();
-        let body = &res.text().await.unwrap();
-        // println!("{:?}", &body);
-        let body: RepoResponse = serde_json::from_str(&body).unwrap();
-        Ok(body)
+            .map_err(|e| GithubError::Upstream(e.to_string()))?;
+
+        let entry = match cached {
+            Some(mut entry) if res.status() == StatusCode::NOT_MODIFIED => {
+                debug!("GitHub returned 304 for {}", url);
+                entry.fetched_at = now;
+                Self::store(redis, key, &entry).await?;
+                return Ok(Self::cached(&entry, CacheStatus::Revalidated, now));
+            }
+            _ => {
+                let res = Self::check_status(res, url)?;
+                let header = |name| {
+                    res.headers()
+                        .get(name)
+                        .and_then(|value| value.to_str().ok())
+                        .map(str::to_string)
+                };
+                let etag = header(ETAG);
+                let last_modified = header(LAST_MODIFIED);
+                let next = header(LINK).as_deref().and_then(next_link);
+                let body = res
+                    .text()
+                    .await
+                    .map_err(|e| GithubError::Upstream(e.to_string()))?;
+                let body = serde_json::from_str::<T>(&body)
+                    .map_err(|e| GithubError::Schema(e.to_string()))?;
+                CacheEntry {
+                    etag,
+                    last_modified,
+                    next,
+                    fetched_at: now,
+                    body,
+                }
+            }
+        };
+        Self::store(redis, key, &entry).await?;
+        Ok(Self::cached(&entry, CacheStatus::Miss, now))
+    }
+
+    // Map GitHub's failure statuses onto `GithubError`
+    fn check_status(res: Response, url: &str) -> Result<Response, GithubError> {
+        let status = res.status();
+        if status.is_success() {
+            return Ok(res);
+        }
+
+        let header = |name: &str| {
+            res.headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.parse::<i64>().ok())
+        };
+        let remaining = header("x-ratelimit-remaining");
+        let retry_after = header("retry-after");
+        if status == StatusCode::TOO_MANY_REQUESTS
+            || (status == StatusCode::FORBIDDEN && (remaining == Some(0) || retry_after.is_some()))
+        {
+            let reset = retry_after
+                .map(|secs| chrono::Utc::now().timestamp() + secs)
+                .or_else(|| header("x-ratelimit-reset"));
+            return Err(GithubError::RateLimited { reset });
+        }
+
+        Err(match status {
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+                GithubError::Unauthorized(format!("GitHub returned {} for {}", status, url))
+            }
+            StatusCode::NOT_FOUND => GithubError::NotFound(url.to_string()),
+            _ => GithubError::Upstream(format!("GitHub returned {} for {}", status, url)),
+        })
+    }
+
+    async fn store<T: Serialize>(
+        redis: &mut RedisManager,
+        key: &str,
+        entry: &CacheEntry<T>,
+    ) -> Result<(), AppError> {
+        redis::cmd("SET")
+            .arg(key)
+            .arg(serde_json::to_string(entry)?)
+            .arg("EX")
+            .arg(CACHE_RETENTION_SECS)
+            .query_async::<ConnectionManager, String>(&mut redis.connection)
+            .await?;
+        Ok(())
+    }
+
+    fn cached<T: Clone>(entry: &CacheEntry<T>, status: CacheStatus, now: i64) -> Cached<T> {
+        Cached {
+            data: entry.body.clone(),
+            next: entry.next.clone(),
+            cache: CacheInfo {
+                status,
+                age: now - entry.fetched_at,
+                fetched_at: entry.fetched_at,
+            },
+        }
+    }
+}
+
+// Repositories the token can't see, or with Actions disabled, are skipped when aggregating
+fn is_inaccessible(error: &AppError) -> bool {
+    matches!(
+        error,
+        AppError::GithubError(GithubError::NotFound(_) | GithubError::Unauthorized(_))
+    )
+}
+
+// Redis key for a cached response; GitHub logins are case-insensitive
+fn cache_key(target: &str, name: &str) -> String {
+    format!("github:cache:{}:{}", target.to_lowercase(), name)
+}
+
+// Cache info for a response assembled from several requests: the oldest part wins
+fn combine_cache(caches: Vec<CacheInfo>) -> CacheInfo {
+    let status = if caches.iter().any(|c| c.status == CacheStatus::Miss) {
+        CacheStatus::Miss
+    } else if caches.iter().any(|c| c.status == CacheStatus::Revalidated) {
+        CacheStatus::Revalidated
+    } else {
+        CacheStatus::Hit
+    };
+    let oldest = caches.iter().max_by_key(|c| c.age);
+    CacheInfo {
+        status,
+        age: oldest.map(|c| c.age).unwrap_or(0),
+        fetched_at: oldest.map(|c| c.fetched_at).unwrap_or_else(|| chrono::Utc::now().timestamp()),
+    }
+}
+
+// The `rel="next"` target of a GitHub `Link` header, allowing extra params and `rel="next last"`
+fn next_link(link: &str) -> Option<String> {
+    link.split(',').find_map(|part| {
+        let mut params = part.split(';');
+        let url = params.next()?.trim().strip_prefix('<')?.strip_suffix('>')?;
+        let is_next = params.filter_map(|param| param.split_once('=')).any(|(key, value)| {
+            key.trim().eq_ignore_ascii_case("rel")
+                && value.trim().trim_matches('"').split_whitespace().any(|rel| rel == "next")
+        });
+        is_next.then(|| url.to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::next_link;
+
+    #[test]
+    fn next_link_picks_next_among_rels() {
+        let link = "<https://api.github.com/user/repos?page=3>; rel=\"prev\", \
+                    <https://api.github.com/user/repos?page=5>; rel=\"next\", \
+                    <https://api.github.com/user/repos?page=9>; rel=\"last\"";
+        assert_eq!(next_link(link).as_deref(), Some("https://api.github.com/user/repos?page=5"));
+    }
+
+    #[test]
+    fn next_link_accepts_multiple_rel_values_and_params() {
+        let link = "<https://example.com/a?page=2>; title=\"more\"; rel=\"next last\"";
+        assert_eq!(next_link(link).as_deref(), Some("https://example.com/a?page=2"));
+    }
+
+    #[test]
+    fn next_link_none_on_last_page() {
+        let link = "<https://example.com/a?page=1>; rel=\"first\", <https://example.com/a?page=8>; rel=\"prev\"";
+        assert_eq!(next_link(link), None);
+        assert_eq!(next_link(""), None);
     }
 }
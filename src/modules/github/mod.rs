@@ -1,5 +1,13 @@
 pub mod entity;
+pub mod feed;
+pub mod graphql;
 pub mod handler;
+pub mod heatmap;
 pub mod manager;
+pub mod provider;
+pub mod repository;
+pub mod webhook;
 
 pub use manager::GithubManager;
+pub use provider::GithubProvider;
+pub use repository::GithubRepository;
@@ -0,0 +1,192 @@
+use std::time::Duration;
+
+use actix_web::web;
+use async_trait::async_trait;
+use envconfig::Envconfig;
+use log::{error, info};
+use serde_json::{json, Value};
+use tokio::sync::Mutex;
+
+use crate::{
+    client::NullClient,
+    config::Config,
+    error::AppError,
+    notify::{Notification, Notifier},
+    provider::Provider,
+};
+
+use super::{
+    handler::{
+        action_health, action_jobs, action_runs, contributions, contributions_svg, events,
+        profile, releases, releases_atom, repos, runner_fleet, runners, webhook,
+    },
+    entity::PublicEvent,
+    GithubManager, GithubRepository,
+};
+
+const WEBHOOK_PAYLOAD_LIMIT: usize = 25 * 1024 * 1024;
+
+pub struct GithubProvider;
+
+#[async_trait]
+impl Provider for GithubProvider {
+    fn name(&self) -> &'static str {
+        "github"
+    }
+
+    fn missing_config(&self, config: &Config) -> Vec<&'static str> {
+        let mut missing = Vec::new();
+        if config.github_secret.is_empty() {
+            missing.push("GITHUB_SECRET");
+        }
+        if config.github_orgs.trim().is_empty() && config.github_users.trim().is_empty() {
+            missing.push("GITHUB_ORGS");
+        }
+        missing
+    }
+
+    fn routes(&self, cfg: &mut web::ServiceConfig) {
+        // Webhook deliveries (push payloads especially) can exceed the default 256 KiB body limit
+        cfg.service(
+            web::resource("/v1/github/webhook")
+                .app_data(web::PayloadConfig::new(WEBHOOK_PAYLOAD_LIMIT))
+                .route(web::post().to(webhook)),
+        );
+        cfg.service(events);
+        // Fixed prefixes before the `{owner}` routes
+        cfg.service(profile);
+        cfg.service(contributions_svg);
+        cfg.service(contributions);
+        cfg.service(action_runs);
+        cfg.service(action_jobs);
+        cfg.service(action_health);
+        cfg.service(releases_atom);
+        cfg.service(releases);
+        cfg.service(runner_fleet);
+        cfg.service(runners);
+        cfg.service(repos);
+    }
+
+    fn refresh_interval(&self) -> Option<Duration> {
+        let config = Config::init_from_env().ok()?;
+        match config.github_runner_poll_interval {
+            0 => None,
+            secs => Some(Duration::from_secs(secs.max(15))),
+        }
+    }
+
+    // Track runner state transitions and alert on runners stuck offline
+    async fn refresh(&self, state: &web::Data<Mutex<NullClient>>) -> Result<(), AppError> {
+        let config = Config::init_from_env()?;
+        let data = state.lock().await;
+        let mut redis = data.redis.clone();
+        let pool = data.postgres.pool.clone();
+        drop(data);
+
+        for owner in GithubManager::owners()? {
+            let polled = match GithubManager::get_runners(&mut redis, &owner).await {
+                Ok(polled) => polled,
+                Err(e) => {
+                    error!("Failed to poll runners for {}: {}", owner.login, e);
+                    continue;
+                }
+            };
+            match GithubRepository::record_runner_states(&pool, &owner.login, &polled.data).await {
+                Ok(0) => {}
+                Ok(changed) => info!("Recorded {} runner transitions for {}", changed, owner.login),
+                Err(e) => error!("Failed to record runner states for {}: {}", owner.login, e),
+            }
+        }
+
+        let threshold = config.github_runner_offline_threshold;
+        for runner in GithubRepository::overdue_offline_runners(&pool, threshold).await? {
+            let minutes = (chrono::Utc::now() - runner.status_since).num_minutes();
+            let notification = Notification {
+                kind: "runner_offline".to_string(),
+                title: format!("Runner {} is offline", runner.name),
+                message: format!(
+                    "Self-hosted runner {} ({}, labels: {}) for {} has been offline for {} minutes.",
+                    runner.name,
+                    runner.os,
+                    runner.labels.join(", "),
+                    runner.owner,
+                    minutes
+                ),
+            };
+            // Only mark as alerted once a sink took it, so a failed delivery is retried next poll
+            if Notifier::send(&notification).await? > 0 {
+                GithubRepository::mark_offline_alerted(&pool, &runner.owner, runner.runner_id).await?;
+            }
+        }
+        Ok(())
+    }
+
+    // Latest activity and a runner count per owner
+    async fn now(&self, state: &web::Data<Mutex<NullClient>>) -> Result<Option<Value>, AppError> {
+        let data = state.lock().await;
+        let mut redis = data.redis.clone();
+        let pool = data.postgres.pool.clone();
+        drop(data);
+
+        let owners = GithubManager::owners()?;
+
+        // Webhook deliveries when there are any, otherwise the newest public event across owners
+        let latest = match GithubRepository::events(&pool, None, None, 1).await?.into_iter().next() {
+            Some(event) => Some(json!({
+                "source": "webhook",
+                "event": event.event,
+                "repo": event.repo,
+                "actor": event.sender,
+                "summary": event.summary,
+                "at": event.received_at.to_rfc3339()
+            })),
+            None => {
+                let mut newest: Option<PublicEvent> = None;
+                for owner in &owners {
+                    match GithubManager::get_public_events(&mut redis, owner).await {
+                        Ok(cached) => {
+                            if let Some(event) = cached.data.into_iter().next() {
+                                if newest.as_ref().is_none_or(|newest| event.created_at > newest.created_at) {
+                                    newest = Some(event);
+                                }
+                            }
+                        }
+                        Err(e) => error!("Failed to fetch public events for {}: {}", owner.login, e),
+                    }
+                }
+                newest.map(|event| {
+                    json!({
+                        "source": "events_api",
+                        "event": event.kind,
+                        "repo": event.repo.name,
+                        "actor": event.actor.login,
+                        "summary": format!("{} {} on {}", event.actor.login, event.kind, event.repo.name),
+                        "at": event.created_at
+                    })
+                })
+            }
+        };
+
+        let mut fleet = Vec::new();
+        for owner in owners {
+            let listed = match GithubManager::get_runners(&mut redis, &owner).await {
+                Ok(cached) => cached.data.runners,
+                Err(e) => {
+                    fleet.push(json!({ "owner": owner.login, "error": e.to_string() }));
+                    continue;
+                }
+            };
+            let online = listed.iter().filter(|runner| runner.status == "online").count();
+            let busy = listed.iter().filter(|runner| runner.busy).count();
+            fleet.push(json!({
+                "owner": owner.login,
+                "total": listed.len(),
+                "online": online,
+                "busy": busy,
+                "offline": listed.len() - online
+            }));
+        }
+
+        Ok(Some(json!({ "latest_event": latest, "runners": fleet })))
+    }
+}
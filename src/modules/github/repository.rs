@@ -0,0 +1,302 @@
+use serde_json::Value;
+use chrono::{DateTime, Utc};
+use sqlx::{types::Json, Pool, Postgres, Transaction};
+
+use crate::error::AppError;
+
+use super::{
+    entity::{percent, GithubEvent, RepoResponse, RunnerResponse, RunnerState, RunnerUptime},
+    webhook::WebhookEvent,
+};
+
+#[derive(Clone)]
+pub struct GithubRepository;
+
+impl GithubRepository {
+    pub async fn record_runners(
+        pool: &Pool<Postgres>,
+        owner: &str,
+        data: &RunnerResponse,
+    ) -> Result<(), AppError> {
+        let mut tx = pool.begin().await?;
+
+        for runner in &data.runners {
+            let labels: Vec<&str> = runner.labels.iter().map(|l| l.name.as_str()).collect();
+            sqlx::query(
+                "INSERT INTO github_runner_snapshots (owner, runner_id, name, os, status, busy, labels)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7)",
+            )
+            .bind(owner)
+            .bind(runner.id)
+            .bind(&runner.name)
+            .bind(&runner.os)
+            .bind(&runner.status)
+            .bind(runner.busy)
+            .bind(&labels)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn record_repos(pool: &Pool<Postgres>, data: &RepoResponse) -> Result<(), AppError> {
+        let mut tx = pool.begin().await?;
+
+        for repo in data {
+            sqlx::query(
+                "INSERT INTO github_repo_snapshots (
+                    repo_id, full_name, language, stargazers_count, forks_count, watchers_count,
+                    open_issues_count, size, archived, pushed_at
+                 ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)",
+            )
+            .bind(repo.id)
+            .bind(&repo.full_name)
+            .bind(&repo.language)
+            .bind(repo.stargazers_count)
+            .bind(repo.forks_count)
+            .bind(repo.watchers_count)
+            .bind(repo.open_issues_count)
+            .bind(repo.size)
+            .bind(repo.archived)
+            .bind(&repo.pushed_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    // Returns false for a redelivery we already stored
+    pub async fn record_event(
+        pool: &Pool<Postgres>,
+        delivery_id: &str,
+        event: &WebhookEvent,
+        payload: &Value,
+    ) -> Result<bool, AppError> {
+        let result = sqlx::query(
+            "INSERT INTO github_events (delivery_id, event, action, repo, sender, summary, payload)
+             VALUES ($1, $2, $3, $4, $5, $6, $7)
+             ON CONFLICT (delivery_id) DO NOTHING",
+        )
+        .bind(delivery_id)
+        .bind(&event.event)
+        .bind(&event.action)
+        .bind(&event.repo)
+        .bind(&event.sender)
+        .bind(&event.summary)
+        .bind(Json(payload))
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    // Most recent events first, optionally for one event type or repository
+    pub async fn events(
+        pool: &Pool<Postgres>,
+        event: Option<&str>,
+        repo: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<GithubEvent>, AppError> {
+        let events = sqlx::query_as::<_, GithubEvent>(
+            "SELECT id, delivery_id, event, action, repo, sender, summary, received_at
+             FROM github_events
+             WHERE ($1::TEXT IS NULL OR event = $1)
+               AND ($2::TEXT IS NULL OR LOWER(repo) = LOWER($2))
+             ORDER BY received_at DESC, id DESC
+             LIMIT $3",
+        )
+        .bind(event)
+        .bind(repo)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+        Ok(events)
+    }
+
+    // Record status/busy transitions for an owner's runners; returns how many runners changed
+    pub async fn record_runner_states(
+        pool: &Pool<Postgres>,
+        owner: &str,
+        data: &RunnerResponse,
+    ) -> Result<u64, AppError> {
+        let mut tx = pool.begin().await?;
+        let now = Utc::now();
+        let known: Vec<(i64, String, bool)> =
+            sqlx::query_as("SELECT runner_id, status, busy FROM github_runner_states WHERE owner = $1")
+                .bind(owner)
+                .fetch_all(&mut *tx)
+                .await?;
+
+        let mut changed = 0;
+        for runner in &data.runners {
+            let labels: Vec<&str> = runner.labels.iter().map(|l| l.name.as_str()).collect();
+            let unchanged = known
+                .iter()
+                .any(|(id, status, busy)| *id == runner.id && *status == runner.status && *busy == runner.busy);
+            if unchanged {
+                sqlx::query(
+                    "UPDATE github_runner_states SET name = $3, os = $4, labels = $5, last_seen_at = $6
+                     WHERE owner = $1 AND runner_id = $2",
+                )
+                .bind(owner)
+                .bind(runner.id)
+                .bind(&runner.name)
+                .bind(&runner.os)
+                .bind(&labels)
+                .bind(now)
+                .execute(&mut *tx)
+                .await?;
+                continue;
+            }
+
+            changed += 1;
+            Self::close_transition(&mut tx, owner, runner.id, now).await?;
+            sqlx::query(
+                "INSERT INTO github_runner_transitions (owner, runner_id, status, busy, started_at)
+                 VALUES ($1, $2, $3, $4, $5)",
+            )
+            .bind(owner)
+            .bind(runner.id)
+            .bind(&runner.status)
+            .bind(runner.busy)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+            sqlx::query(
+                "INSERT INTO github_runner_states (
+                    owner, runner_id, name, os, labels, status, busy, status_since, last_seen_at
+                 ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $8)
+                 ON CONFLICT (owner, runner_id) DO UPDATE SET
+                    name = EXCLUDED.name, os = EXCLUDED.os, labels = EXCLUDED.labels,
+                    status = EXCLUDED.status, busy = EXCLUDED.busy,
+                    last_seen_at = EXCLUDED.last_seen_at,
+                    -- Busy flips don't restart the offline clock
+                    status_since = CASE
+                        WHEN github_runner_states.status = EXCLUDED.status
+                        THEN github_runner_states.status_since
+                        ELSE EXCLUDED.status_since
+                    END,
+                    offline_alerted_at = CASE
+                        WHEN github_runner_states.status = EXCLUDED.status
+                        THEN github_runner_states.offline_alerted_at
+                    END",
+            )
+            .bind(owner)
+            .bind(runner.id)
+            .bind(&runner.name)
+            .bind(&runner.os)
+            .bind(&labels)
+            .bind(&runner.status)
+            .bind(runner.busy)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        // Runners no longer registered stop accruing time
+        for (id, _, _) in &known {
+            if !data.runners.iter().any(|runner| runner.id == *id) {
+                changed += 1;
+                Self::close_transition(&mut tx, owner, *id, now).await?;
+                sqlx::query("DELETE FROM github_runner_states WHERE owner = $1 AND runner_id = $2")
+                    .bind(owner)
+                    .bind(id)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+        }
+
+        tx.commit().await?;
+        Ok(changed)
+    }
+
+    async fn close_transition(
+        tx: &mut Transaction<'_, Postgres>,
+        owner: &str,
+        runner_id: i64,
+        now: DateTime<Utc>,
+    ) -> Result<(), AppError> {
+        sqlx::query(
+            "UPDATE github_runner_transitions SET ended_at = $3
+             WHERE owner = $1 AND runner_id = $2 AND ended_at IS NULL",
+        )
+        .bind(owner)
+        .bind(runner_id)
+        .bind(now)
+        .execute(&mut **tx)
+        .await?;
+        Ok(())
+    }
+
+    // Runners offline for longer than the threshold that haven't been alerted on yet
+    pub async fn overdue_offline_runners(
+        pool: &Pool<Postgres>,
+        threshold_secs: i64,
+    ) -> Result<Vec<RunnerState>, AppError> {
+        let runners = sqlx::query_as::<_, RunnerState>(
+            "SELECT owner, runner_id, name, os, labels, status, busy, status_since, last_seen_at
+             FROM github_runner_states
+             WHERE status = 'offline'
+               AND offline_alerted_at IS NULL
+               AND status_since < NOW() - make_interval(secs => $1)",
+        )
+        .bind(threshold_secs as f64)
+        .fetch_all(pool)
+        .await?;
+        Ok(runners)
+    }
+
+    pub async fn mark_offline_alerted(
+        pool: &Pool<Postgres>,
+        owner: &str,
+        runner_id: i64,
+    ) -> Result<(), AppError> {
+        sqlx::query(
+            "UPDATE github_runner_states SET offline_alerted_at = NOW()
+             WHERE owner = $1 AND runner_id = $2",
+        )
+        .bind(owner)
+        .bind(runner_id)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    // Online and busy time per runner since `since`
+    pub async fn runner_uptime(
+        pool: &Pool<Postgres>,
+        owner: &str,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<RunnerUptime>, AppError> {
+        let mut runners = sqlx::query_as::<_, RunnerUptime>(
+            "WITH spans AS (
+                SELECT runner_id, status, busy,
+                       EXTRACT(EPOCH FROM (COALESCE(ended_at, NOW()) - GREATEST(started_at, $2)))::FLOAT8 AS seconds
+                FROM github_runner_transitions
+                WHERE owner = $1 AND COALESCE(ended_at, NOW()) > $2
+             )
+             SELECT s.runner_id, s.name, s.os, s.labels, s.status, s.busy, s.status_since,
+                    COALESCE(SUM(spans.seconds), 0)::FLOAT8 AS tracked_seconds,
+                    COALESCE(SUM(spans.seconds) FILTER (WHERE spans.status = 'online'), 0)::FLOAT8 AS online_seconds,
+                    COALESCE(SUM(spans.seconds) FILTER (WHERE spans.status = 'online' AND spans.busy), 0)::FLOAT8 AS busy_seconds
+             FROM github_runner_states s
+             LEFT JOIN spans ON spans.runner_id = s.runner_id
+             WHERE s.owner = $1
+             GROUP BY s.runner_id, s.name, s.os, s.labels, s.status, s.busy, s.status_since
+             ORDER BY s.name",
+        )
+        .bind(owner)
+        .bind(since)
+        .fetch_all(pool)
+        .await?;
+
+        for runner in &mut runners {
+            runner.uptime_percent = percent(runner.online_seconds, runner.tracked_seconds);
+            runner.utilization_percent = percent(runner.busy_seconds, runner.online_seconds);
+        }
+        Ok(runners)
+    }
+}
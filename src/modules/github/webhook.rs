@@ -0,0 +1,217 @@
+use hmac::{Hmac, Mac};
+use serde_json::Value;
+use sha2::Sha256;
+
+use crate::error::AppError;
+
+// Check `X-Hub-Signature-256` (`sha256=<hex>`) against the raw request body
+pub fn verify_signature(secret: &str, signature: Option<&str>, body: &[u8]) -> Result<(), AppError> {
+    if secret.is_empty() {
+        return Err(AppError::Unauthorized("Webhook secret not configured".to_string()));
+    }
+    let signature = signature
+        .and_then(|signature| signature.strip_prefix("sha256="))
+        .ok_or_else(|| AppError::Unauthorized("Missing webhook signature".to_string()))?;
+    let signature = hex::decode(signature)
+        .map_err(|_| AppError::Unauthorized("Invalid webhook signature".to_string()))?;
+
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .map_err(|_| AppError::Unauthorized("Invalid webhook secret".to_string()))?;
+    mac.update(body);
+    mac.verify_slice(&signature)
+        .map_err(|_| AppError::Unauthorized("Invalid webhook signature".to_string()))
+}
+
+// The parts of a delivery we store and act on
+#[derive(Debug, Clone)]
+pub struct WebhookEvent {
+    pub event: String,
+    pub action: Option<String>,
+    pub repo: Option<String>,
+    pub owner: Option<String>,
+    pub sender: Option<String>,
+    pub summary: String,
+}
+
+impl WebhookEvent {
+    pub fn parse(event: &str, payload: &Value) -> Self {
+        let text = |pointer: &str| payload.pointer(pointer).and_then(Value::as_str).map(str::to_string);
+        let action = text("/action");
+        let repo = text("/repository/full_name");
+        let owner = text("/repository/owner/login").or_else(|| text("/organization/login"));
+        let sender = text("/sender/login");
+
+        let actor = sender.as_deref().unwrap_or("someone");
+        let target = repo.as_deref().unwrap_or("a repository");
+        let summary = match event {
+            "push" => {
+                let commits = payload["commits"].as_array().map(Vec::len).unwrap_or(0);
+                let branch = text("/ref").unwrap_or_default();
+                let branch = branch.trim_start_matches("refs/heads/");
+                format!(
+                    "{} pushed {} commit{} to {} on {}",
+                    actor,
+                    commits,
+                    if commits == 1 { "" } else { "s" },
+                    branch,
+                    target
+                )
+            }
+            "workflow_run" => format!(
+                "Workflow {} {} on {}{}",
+                text("/workflow_run/name").unwrap_or_default(),
+                action.as_deref().unwrap_or("updated"),
+                target,
+                text("/workflow_run/conclusion")
+                    .map(|conclusion| format!(" ({})", conclusion))
+                    .unwrap_or_default()
+            ),
+            "workflow_job" => format!(
+                "Job {} {} on {}{}",
+                text("/workflow_job/name").unwrap_or_default(),
+                action.as_deref().unwrap_or("updated"),
+                target,
+                text("/workflow_job/runner_name")
+                    .map(|runner| format!(" (runner {})", runner))
+                    .unwrap_or_default()
+            ),
+            "release" => format!(
+                "{} {} release {} on {}",
+                actor,
+                action.as_deref().unwrap_or("updated"),
+                text("/release/tag_name").unwrap_or_default(),
+                target
+            ),
+            "star" => match action.as_deref() {
+                Some("deleted") => format!("{} unstarred {}", actor, target),
+                _ => format!("{} starred {}", actor, target),
+            },
+            other => format!("{} event on {}", other, target),
+        };
+
+        Self {
+            event: event.to_string(),
+            action,
+            repo,
+            owner,
+            sender,
+            summary,
+        }
+    }
+
+    // Redis cache patterns made stale by this event, matching `GithubManager`'s keys
+    pub fn stale_cache_patterns(&self) -> Vec<String> {
+        let repo = self.repo.as_deref().map(str::to_lowercase);
+        let owner = self.owner.as_deref().map(str::to_lowercase);
+        let sender = self.sender.as_deref().map(str::to_lowercase);
+        let mut patterns = Vec::new();
+        let mut add = |target: &Option<String>, suffix: &str| {
+            if let Some(target) = target {
+                patterns.push(format!("github:cache:{}:{}", target, suffix));
+            }
+        };
+
+        match self.event.as_str() {
+            "workflow_run" => add(&repo, "runs:*"),
+            // Jobs are how GitHub reports self-hosted runners picking up and finishing work
+            "workflow_job" => {
+                add(&repo, "runs:*");
+                add(&repo, "runners");
+                add(&owner, "runners");
+            }
+            "push" => {
+                add(&repo, "runs:*");
+                add(&owner, "repos:*");
+                add(&sender, "profile");
+                add(&sender, "contributions*");
+            }
+            "release" => add(&repo, "releases*"),
+            "star" => {
+                add(&owner, "repos:*");
+                add(&owner, "profile");
+            }
+            _ => {}
+        }
+        patterns
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const SECRET: &str = "It's a Secret to Everybody";
+    const BODY: &[u8] = b"Hello, World!";
+    // Example delivery from GitHub's webhook validation docs
+    const SIGNATURE: &str = "sha256=757107ea0eb2509fc211221cce984b8a37570b6d7586c22c46f4379c8b043e17";
+
+    fn unauthorized(result: Result<(), AppError>) -> bool {
+        matches!(result, Err(AppError::Unauthorized(_)))
+    }
+
+    #[test]
+    fn verify_signature_accepts_valid() {
+        assert!(verify_signature(SECRET, Some(SIGNATURE), BODY).is_ok());
+    }
+
+    #[test]
+    fn verify_signature_rejects_tampered_body() {
+        assert!(unauthorized(verify_signature(SECRET, Some(SIGNATURE), b"Hello, World?")));
+        assert!(unauthorized(verify_signature("another secret", Some(SIGNATURE), BODY)));
+    }
+
+    #[test]
+    fn verify_signature_rejects_missing() {
+        assert!(unauthorized(verify_signature(SECRET, None, BODY)));
+        assert!(unauthorized(verify_signature("", Some(SIGNATURE), BODY)));
+    }
+
+    #[test]
+    fn verify_signature_requires_sha256_prefix() {
+        let bare = SIGNATURE.trim_start_matches("sha256=");
+        assert!(unauthorized(verify_signature(SECRET, Some(bare), BODY)));
+        assert!(unauthorized(verify_signature(SECRET, Some(&format!("sha1={}", bare)), BODY)));
+        assert!(unauthorized(verify_signature(SECRET, Some("sha256=not-hex"), BODY)));
+    }
+
+    #[test]
+    fn stale_cache_patterns_lowercase_targets() {
+        let payload = json!({
+            "repository": { "full_name": "Octo/Repo", "owner": { "login": "Octo" } },
+            "sender": { "login": "Alice" },
+            "commits": []
+        });
+        let event = WebhookEvent::parse("push", &payload);
+        assert_eq!(
+            event.stale_cache_patterns(),
+            vec![
+                "github:cache:octo/repo:runs:*",
+                "github:cache:octo:repos:*",
+                "github:cache:alice:profile",
+                "github:cache:alice:contributions*",
+            ]
+        );
+    }
+
+    #[test]
+    fn stale_cache_patterns_workflow_job_clears_runners() {
+        let payload = json!({
+            "action": "completed",
+            "repository": { "full_name": "octo/repo", "owner": { "login": "octo" } }
+        });
+        let event = WebhookEvent::parse("workflow_job", &payload);
+        assert_eq!(
+            event.stale_cache_patterns(),
+            vec!["github:cache:octo/repo:runs:*", "github:cache:octo/repo:runners", "github:cache:octo:runners"]
+        );
+    }
+
+    #[test]
+    fn stale_cache_patterns_skip_missing_targets() {
+        let event = WebhookEvent::parse("release", &json!({}));
+        assert!(event.stale_cache_patterns().is_empty());
+        let event = WebhookEvent::parse("ping", &json!({ "repository": { "full_name": "octo/repo" } }));
+        assert!(event.stale_cache_patterns().is_empty());
+    }
+}
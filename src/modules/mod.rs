@@ -1,17 +1,71 @@
 pub mod duolingo;
 pub mod github;
+pub mod now;
 pub mod spotify;
+pub mod wakatime;
 
-use actix_web::{get, http::Error, HttpResponse};
+use std::sync::Arc;
+
+use actix_web::{get, http::Error, web, HttpResponse};
+use futures::future::join_all;
+use serde_json::json;
+use tokio::sync::Mutex;
+
+use crate::{
+    client::NullClient,
+    provider::{Provider, ProviderHealth, ProviderRegistry},
+};
+
+// Every known provider, in mount order
+pub fn providers() -> Vec<Arc<dyn Provider>> {
+    vec![
+        Arc::new(spotify::SpotifyProvider::default()),
+        Arc::new(github::GithubProvider),
+        Arc::new(duolingo::DuoProvider),
+        Arc::new(wakatime::WakaProvider),
+    ]
+}
 
 #[get("/")]
-async fn index() -> Result<HttpResponse, Error> {
-    Ok(HttpResponse::Ok().body("Ok"))
+async fn index(registry: web::Data<ProviderRegistry>) -> Result<HttpResponse, Error> {
+    Ok(HttpResponse::Ok().json(json!({
+        "success": true,
+        "data": {
+            "name": env!("CARGO_PKG_NAME"),
+            "version": env!("CARGO_PKG_VERSION"),
+            "providers": registry.names()
+        }
+    })))
 }
 
+// Always 200 while the process is up; per-provider status is reported in the body
 #[get("/health")]
-async fn health() -> Result<HttpResponse, Error> {
-    Ok(HttpResponse::NoContent().finish())
+async fn health(
+    state: web::Data<Mutex<NullClient>>,
+    registry: web::Data<ProviderRegistry>,
+) -> Result<HttpResponse, Error> {
+    let checks = join_all(registry.providers().iter().map(|provider| {
+        let state = &state;
+        async move { (provider.name(), provider.health(state).await) }
+    }))
+    .await;
+
+    let healthy = checks.iter().all(|(_, result)| result.is_ok());
+    let providers: serde_json::Map<String, serde_json::Value> = checks
+        .into_iter()
+        .map(|(name, result)| {
+            let status = match result {
+                Ok(ProviderHealth::Up) => json!({ "status": "up" }),
+                Ok(ProviderHealth::NotConfigured(reason)) => {
+                    json!({ "status": "not_configured", "reason": reason })
+                }
+                Err(e) => json!({ "status": "down", "error": e.to_string() }),
+            };
+            (name.to_string(), status)
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(json!({ "healthy": healthy, "providers": providers })))
 }
 
 pub async fn default() -> Result<HttpResponse, Error> {
@@ -0,0 +1,78 @@
+use std::time::Duration;
+
+use actix_web::{get, web, HttpResponse};
+use chrono::Utc;
+use envconfig::Envconfig;
+use futures::future::join_all;
+use redis::{aio::ConnectionManager, AsyncCommands};
+use serde_json::{json, Map, Value};
+use tokio::sync::Mutex;
+
+use crate::{client::NullClient, config::Config, error::AppError, provider::ProviderRegistry};
+
+const NOW_CACHE_KEY: &str = "now:summary";
+// A slow provider shouldn't hold up the whole response
+const SECTION_TIMEOUT: Duration = Duration::from_secs(5);
+
+// What we're up to right now, one section per loaded provider with a `now` hook
+#[get("/v1/now")]
+async fn get_now(
+    state: web::Data<Mutex<NullClient>>,
+    registry: web::Data<ProviderRegistry>,
+) -> Result<HttpResponse, AppError> {
+    let mut redis = state.lock().await.redis.clone();
+
+    let cached: Option<String> = redis.connection.get(NOW_CACHE_KEY).await?;
+    if let Some(body) = cached {
+        return Ok(HttpResponse::Ok()
+            .insert_header(("Content-Type", "application/json"))
+            .body(body));
+    }
+
+    let sections = join_all(registry.providers().iter().map(|provider| {
+        let state = &state;
+        async move {
+            let result = tokio::time::timeout(SECTION_TIMEOUT, provider.now(state)).await;
+            // Stamped when this section finishes, not when the slowest one does
+            (provider.name(), result, Utc::now())
+        }
+    }))
+    .await;
+
+    // Failures become a per-section `error` instead of failing the whole response
+    let mut data = Map::new();
+    for (name, result, fetched_at) in sections {
+        let (section, error) = match result {
+            Ok(Ok(None)) => continue,
+            Ok(Ok(Some(section))) => (section, None),
+            Ok(Err(e)) => (Value::Null, Some(e.to_string())),
+            Err(_) => (Value::Null, Some("timed out".to_string())),
+        };
+        data.insert(
+            name.to_string(),
+            json!({ "data": section, "error": error, "fetched_at": fetched_at.to_rfc3339() }),
+        );
+    }
+
+    let body = json!({
+        "success": true,
+        "fetched_at": Utc::now().to_rfc3339(),
+        "data": data
+    })
+    .to_string();
+
+    let config = Config::init_from_env()?;
+    if config.now_ttl > 0 {
+        redis::cmd("SET")
+            .arg(NOW_CACHE_KEY)
+            .arg(&body)
+            .arg("EX")
+            .arg(config.now_ttl)
+            .query_async::<ConnectionManager, String>(&mut redis.connection)
+            .await?;
+    }
+
+    Ok(HttpResponse::Ok()
+        .insert_header(("Content-Type", "application/json"))
+        .body(body))
+}
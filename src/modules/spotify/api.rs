@@ -0,0 +1,191 @@
+use std::sync::OnceLock;
+
+use envconfig::Envconfig;
+use log::info;
+use redis::{aio::ConnectionManager, AsyncCommands};
+use reqwest::{
+    header::{ACCEPT, AUTHORIZATION, RETRY_AFTER},
+    Method, RequestBuilder, Response, StatusCode,
+};
+use serde::de::DeserializeOwned;
+use tokio::sync::Mutex;
+
+use crate::{config::Config, db::redis::RedisManager, error::{AppError, SpotifyError}};
+
+use super::entity::TokenResponse;
+
+// Access tokens are dropped from Redis this long before Spotify expires them
+const EXPIRY_MARGIN_SECS: i64 = 60;
+
+// Serializes refreshes so concurrent requests don't race on a rotated refresh token
+static REFRESH_LOCK: Mutex<()> = Mutex::const_new(());
+
+fn http() -> &'static reqwest::Client {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    CLIENT.get_or_init(reqwest::Client::new)
+}
+
+// Redis key holding a value for one linked account
+pub fn account_key(account: &str, name: &str) -> String {
+    format!("spotify:{}:{}", account, name)
+}
+
+// Authenticated Spotify Web API client shared by every `SpotifyManager` call
+#[derive(Clone)]
+pub struct SpotifyApi;
+
+impl SpotifyApi {
+    pub async fn get(redis: &mut RedisManager, account: &str, url: &str) -> Result<Response, AppError> {
+        Self::send(redis, account, Method::GET, url, |request| request).await
+    }
+
+    // Send a request with a valid access token, refreshing and retrying once on 401
+    pub async fn send<F>(
+        redis: &mut RedisManager,
+        account: &str,
+        method: Method,
+        url: &str,
+        build: F,
+    ) -> Result<Response, AppError>
+    where
+        F: Fn(RequestBuilder) -> RequestBuilder,
+    {
+        let token = Self::access_token(redis, account).await?;
+        let response = build(Self::request(method.clone(), url, &token)).send().await?;
+        if response.status() != StatusCode::UNAUTHORIZED {
+            return Ok(response);
+        }
+
+        info!("Spotify rejected access token, refreshing and retrying");
+        let token = Self::refresh(redis, account, Some(&token)).await?;
+        let response = build(Self::request(method, url, &token)).send().await?;
+        Ok(response)
+    }
+
+    // GET a JSON body, failing on any non-2xx status
+    pub async fn get_json<T: DeserializeOwned>(
+        redis: &mut RedisManager,
+        account: &str,
+        url: &str,
+    ) -> Result<T, AppError> {
+        let response = Self::check(Self::get(redis, account, url).await?).await?;
+        Ok(response.json().await?)
+    }
+
+    // Turn a non-2xx answer into a `SpotifyError`, keeping `Retry-After` on 429
+    pub async fn check(response: Response) -> Result<Response, AppError> {
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            let retry_after = response
+                .headers()
+                .get(RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.trim().parse().ok());
+            return Err(SpotifyError::RateLimited { retry_after }.into());
+        }
+        // Spotify errors look like `{"error": {"status": 404, "message": "..."}}`
+        let error: serde_json::Value = response.json().await.unwrap_or_default();
+        let message = error["error"]["message"].as_str().unwrap_or("Unknown error").to_string();
+        Err(SpotifyError::Status {
+            status: status.as_u16(),
+            message,
+        }
+        .into())
+    }
+
+    // Current access token, refreshed ahead of expiry when it has lapsed from Redis
+    pub async fn access_token(redis: &mut RedisManager, account: &str) -> Result<String, AppError> {
+        let token: Option<String> = redis.connection.get(account_key(account, "access_token")).await?;
+        match token {
+            Some(token) => Ok(token),
+            None => Self::refresh(redis, account, None).await,
+        }
+    }
+
+    // Exchange the refresh token for a new access token, storing a rotated refresh token
+    async fn refresh(
+        redis: &mut RedisManager,
+        account: &str,
+        rejected: Option<&str>,
+    ) -> Result<String, AppError> {
+        let _guard = REFRESH_LOCK.lock().await;
+
+        // Another request may have refreshed while we waited for the lock
+        let current: Option<String> = redis.connection.get(account_key(account, "access_token")).await?;
+        if let Some(current) = current {
+            if rejected != Some(current.as_str()) {
+                return Ok(current);
+            }
+        }
+
+        let refresh_token: Option<String> =
+            redis.connection.get(account_key(account, "refresh_token")).await?;
+        let Some(refresh_token) = refresh_token else {
+            return Err(SpotifyError::Other("Not authenticated".to_string()).into());
+        };
+
+        let config = Config::init_from_env()?;
+        let mut params = vec![
+            ("grant_type", "refresh_token"),
+            ("refresh_token", refresh_token.as_str()),
+            ("client_id", config.spotify_client_id.as_str()),
+        ];
+        // PKCE clients refresh without a secret
+        if !config.spotify_client_secret.is_empty() {
+            params.push(("client_secret", config.spotify_client_secret.as_str()));
+        }
+
+        let response = http()
+            .post("https://accounts.spotify.com/api/token")
+            .form(&params)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(SpotifyError::Other(format!(
+                "Token refresh failed ({}): {}",
+                status, body
+            )).into());
+        }
+
+        let token: TokenResponse = response.json().await?;
+        Self::store_access_token(redis, account, &token.access_token, token.expires_in).await?;
+        if let Some(rotated) = &token.refresh_token {
+            redis::cmd("SET")
+                .arg(account_key(account, "refresh_token"))
+                .arg(rotated)
+                .query_async::<ConnectionManager, String>(&mut redis.connection)
+                .await?;
+        }
+
+        info!("Refreshed Spotify access token for {}", account);
+        Ok(token.access_token)
+    }
+
+    pub async fn store_access_token(
+        redis: &mut RedisManager,
+        account: &str,
+        token: &str,
+        expires_in: i64,
+    ) -> Result<(), AppError> {
+        redis::cmd("SET")
+            .arg(account_key(account, "access_token"))
+            .arg(token)
+            .arg("EX")
+            .arg((expires_in - EXPIRY_MARGIN_SECS).max(1))
+            .query_async::<ConnectionManager, String>(&mut redis.connection)
+            .await?;
+        Ok(())
+    }
+
+    pub fn request(method: Method, url: &str, token: &str) -> RequestBuilder {
+        http()
+            .request(method, url)
+            .header(AUTHORIZATION, format!("Bearer {}", token))
+            .header(ACCEPT, "application/json")
+    }
+}
@@ -1,3 +1,4 @@
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 #[derive(Debug, Serialize, Deserialize)]
@@ -5,11 +6,18 @@ pub struct AuthData {
     pub code: String,
     pub grant_type: String,
     pub redirect_uri: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code_verifier: Option<String>,
 }
 
+// Spotify redirects back with either `code` or `error`, plus the `state` we issued
 #[derive(Debug, Deserialize, Clone)]
 pub struct AuthQuery {
-    pub code: String,
+    pub code: Option<String>,
+    pub state: Option<String>,
+    pub error: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -21,6 +29,20 @@ pub struct SpotifyToken {
     pub scope: String,
 }
 
+// Linked account, identified by Spotify user id
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpotifyProfile {
+    pub id: String,
+    pub display_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpotifyAccount {
+    pub id: String,
+    pub display_name: Option<String>,
+    pub default: bool,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TokenError {
     pub error: String,
@@ -71,7 +93,7 @@ pub struct Item {
     pub name: String,
     pub popularity: i64,
     #[serde(rename = "preview_url")]
-    pub preview_url: String,
+    pub preview_url: Option<String>,
     #[serde(rename = "track_number")]
     pub track_number: i64,
     #[serde(rename = "type")]
@@ -142,11 +164,20 @@ pub struct TokenResponse {
     pub access_token: String,
     pub token_type: String,
     pub expires_in: i64,
+    pub refresh_token: Option<String>,
     pub scope: String,
 }
 
+// What the player is doing; no device or no track are normal idle states
+#[derive(Debug, Clone)]
+pub enum Playback {
+    Playing(Box<RealTimeSongInfo>),
+    NoDevice,
+    NoTrack,
+}
+
 // Enhanced real-time song information structures
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RealTimeSongInfo {
     pub track: TrackInfo,
     pub playback: PlaybackInfo,
@@ -154,7 +185,7 @@ pub struct RealTimeSongInfo {
     pub context: Option<ContextInfo>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TrackInfo {
     pub id: String,
     pub name: String,
@@ -168,7 +199,7 @@ pub struct TrackInfo {
     pub images: Vec<Image>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PlaybackInfo {
     pub is_playing: bool,
     pub progress_ms: i64,
@@ -179,26 +210,28 @@ pub struct PlaybackInfo {
     pub volume_percent: Option<i32>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DeviceInfo {
     pub id: String,
     pub is_active: bool,
     pub is_private_session: bool,
     pub is_restricted: bool,
     pub name: String,
+    #[serde(alias = "type")]
     pub device_type: String,
     pub volume_percent: i32,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ContextInfo {
     pub uri: String,
     pub href: String,
     pub external_urls: ExternalUrls,
+    #[serde(alias = "type")]
     pub context_type: String,
 }
 
-// Player state structure (read-only for dashboard)
+// Player state structure
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PlayerState {
     pub device: Option<DeviceInfo>,
@@ -212,6 +245,26 @@ pub struct PlayerState {
     pub currently_playing_type: String,
 }
 
+// Playback control request bodies
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct PlayRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context_uri: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uris: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position_ms: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TransferRequest {
+    pub device_id: String,
+    #[serde(default)]
+    pub play: bool,
+}
+
 // Queue management structures
 #[derive(Debug, Serialize, Deserialize)]
 pub struct QueueInfo {
@@ -219,10 +272,125 @@ pub struct QueueInfo {
     pub queue: Vec<Item>,
 }
 
-// WebSocket real-time update structure
-#[derive(Debug, Serialize, Deserialize)]
+// Real-time update pushed over SSE and WebSocket streams
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RealTimeUpdate {
     pub event_type: String,
     pub timestamp: i64,
     pub data: serde_json::Value,
 }
+
+// Recently played structures, stored as listening history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecentlyPlayed {
+    pub items: Vec<PlayHistory>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayHistory {
+    pub track: PlayedTrack,
+    pub played_at: DateTime<Utc>,
+    pub context: Option<PlayContext>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayedTrack {
+    pub id: Option<String>,
+    pub name: String,
+    pub duration_ms: i64,
+    pub artists: Vec<PlayedArtist>,
+    pub album: PlayedAlbum,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayedArtist {
+    pub id: Option<String>,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayedAlbum {
+    pub id: Option<String>,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayContext {
+    #[serde(rename = "type")]
+    pub context_type: String,
+    pub uri: String,
+}
+
+// A play as stored in the listening history
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct StoredPlay {
+    pub played_at: DateTime<Utc>,
+    pub track_id: String,
+    pub track_name: String,
+    pub artists: sqlx::types::Json<Vec<PlayedArtist>>,
+    pub album_id: String,
+    pub album_name: String,
+    pub context_uri: Option<String>,
+    pub context_type: Option<String>,
+    pub duration_ms: i64,
+}
+
+// Artist metadata used for genre analytics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArtistGenres {
+    pub id: String,
+    pub name: String,
+    pub genres: Vec<String>,
+}
+
+// Listening analytics computed from the stored history
+#[derive(Debug, Serialize)]
+pub struct ListeningStats {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub timezone: String,
+    pub total_plays: i64,
+    pub minutes_listened: f64,
+    pub top_tracks: Vec<TopItem>,
+    pub top_artists: Vec<TopItem>,
+    pub top_albums: Vec<TopItem>,
+    pub top_genres: Vec<TopItem>,
+    pub by_hour: Vec<ListeningBucket>,
+    pub by_weekday: Vec<ListeningBucket>,
+    pub heatmap: Vec<HeatmapCell>,
+    pub longest_streak: Option<Streak>,
+    pub current_streak: Option<Streak>,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct TopItem {
+    pub id: Option<String>,
+    pub name: String,
+    pub plays: i64,
+    pub minutes: f64,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct ListeningBucket {
+    pub bucket: i32,
+    pub plays: i64,
+    pub minutes: f64,
+}
+
+// ISO weekday (1 = Monday) by hour of day
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct HeatmapCell {
+    pub weekday: i32,
+    pub hour: i32,
+    pub plays: i64,
+    pub minutes: f64,
+}
+
+// Consecutive days with at least one play
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct Streak {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub days: i64,
+    pub current: bool,
+}
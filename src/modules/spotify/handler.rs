@@ -1,58 +1,54 @@
-use actix_web::{get, web, HttpResponse};
-use envconfig::Envconfig;
-use redis::aio::ConnectionManager;
+use actix_web::{delete, get, http::StatusCode, put, routes, web, HttpRequest, HttpResponse};
+use chrono::{DateTime, Utc};
+use log::error;
 use serde_json::json;
 use tokio::sync::Mutex;
 
 use crate::{
+    auth::Admin,
     client::NullClient,
-    config::Config,
     error::AppError,
     modules::spotify::{
-        entity::{AuthData, AuthQuery, Root, SpotifyToken, TokenError},
-        SpotifyManager,
+        entity::{AuthQuery, PlayRequest, RecentlyPlayed, TransferRequest},
+        oauth::SpotifyOAuth,
+        stream::PlaybackHub,
+        SpotifyManager, SpotifyRepository,
     },
 };
 
+#[routes]
 #[get("/v1/spotify")]
-async fn current(data: web::Data<Mutex<NullClient>>) -> Result<HttpResponse, AppError> {
+#[get("/v1/spotify/{account}/current")]
+async fn current(
+    path: web::Path<AccountPath>,
+    data: web::Data<Mutex<NullClient>>,
+) -> Result<HttpResponse, AppError> {
     let data = data.lock().await;
     let mut redis = data.redis.clone();
     drop(data); // Release the lock early
+    let account = SpotifyManager::resolve_account(&mut redis, path.account.as_deref()).await?;
     
-    SpotifyManager::get_spotify_current(&mut redis).await?;
-    let current: String = redis::cmd("GET")
-        .arg("spotify:now_playing")
-        .query_async::<ConnectionManager, String>(&mut redis.connection)
-        .await?;
-    let playing: Root = serde_json::from_str(&current)?;
+    let playing = SpotifyManager::get_spotify_current(&mut redis, &account).await?;
     Ok(HttpResponse::Ok()
         .insert_header(("Content-Type", "application/json"))
         .body(json!({"success": true, "data": playing}).to_string()))
 }
 
+// Admin only: the signed state it issues is what lets a callback link an account
 #[get("/v1/spotify/auth")]
-async fn authorize(data: web::Data<Mutex<NullClient>>) -> Result<HttpResponse, AppError> {
+async fn authorize(
+    _admin: Admin,
+    data: web::Data<Mutex<NullClient>>,
+) -> Result<HttpResponse, AppError> {
     let data = data.lock().await;
     let mut redis = data.redis.clone();
     drop(data); // Release the lock early
 
-    if !SpotifyManager::check_spotify_access(&mut redis).await? {
-        let config = Config::init_from_env()?;
-
-        let scope = "user-read-playback-state+user-read-currently-playing";
-        let redirect_uri = config.spotify_redirect_uri;
-        let url = format!("https://accounts.spotify.com/authorize?client_id={}&response_type=code&scope={}&redirect_uri={}", config.spotify_client_id, scope, redirect_uri);
-        let json = json!({ "info": "Click the URL to authorize the app", "url": url });
-        Ok(HttpResponse::Ok()
-            .append_header(("Content-type", "application/json"))
-            .json(json))
-    } else {
-        let json = json!({ "error": "Application already authorized!" });
-        Ok(HttpResponse::Ok()
-            .append_header(("Content-type", "application/json"))
-            .json(json))
-    }
+    let url = SpotifyOAuth::authorize_url(&mut redis).await?;
+    let json = json!({ "info": "Click the URL to authorize the app", "url": url });
+    Ok(HttpResponse::Ok()
+        .append_header(("Content-type", "application/json"))
+        .json(json))
 }
 
 #[get("/v1/spotify/callback")]
@@ -63,47 +59,72 @@ async fn callback(
     let data = data.lock().await;
     let mut redis = data.redis.clone();
     drop(data); // Release the lock early
-    
-    let config = Config::init_from_env()?;
-
-    let code = &info.code;
-    let redirect_uri = config.spotify_redirect_uri;
-    let auth_data = AuthData {
-        code: code.into(),
-        grant_type: "authorization_code".into(),
-        redirect_uri: redirect_uri.into(),
-    };
 
-    let form_data = serde_urlencoded::to_string(&auth_data)?;
-
-    let response = reqwest::Client::new()
-        .post(format!("https://accounts.spotify.com/api/token?{}", form_data))
-        .header("Content-Type", "application/x-www-form-urlencoded")
-        .header("Content-Length", "0")
-        .basic_auth(config.spotify_client_id, Some(config.spotify_client_secret))
-        .send()
-        .await?
-        .text()
-        .await?;
-
-    if !response.is_empty() {
-        let token: SpotifyToken = serde_json::from_str(&response)?;
-        SpotifyManager::store_spotify_creds(&mut redis, &token).await?;
-        Ok(HttpResponse::NoContent().finish())
-    } else {
-        let error: TokenError = serde_json::from_str(&response)?;
-        Ok(HttpResponse::InternalServerError().json(error))
+    if let Some(error) = &info.error {
+        let message = match error.as_str() {
+            "access_denied" => "Access to your Spotify account was denied, so nothing was linked.".to_string(),
+            other => format!("Spotify returned an error: {}", other),
+        };
+        return Ok(auth_error_page(StatusCode::FORBIDDEN, &message));
     }
+    let Some(state) = &info.state else {
+        return Ok(auth_error_page(StatusCode::BAD_REQUEST, "The authorization request is missing its state."));
+    };
+    let verifier = match SpotifyOAuth::verify_state(&mut redis, state).await {
+        Ok(verifier) => verifier,
+        Err(AppError::BadRequest(_)) => {
+            return Ok(auth_error_page(
+                StatusCode::BAD_REQUEST,
+                "This authorization link is invalid or has expired. Please start again.",
+            ))
+        }
+        Err(e) => return Err(e),
+    };
+    let Some(code) = &info.code else {
+        return Ok(auth_error_page(StatusCode::BAD_REQUEST, "Spotify did not return an authorization code."));
+    };
+
+    let token = SpotifyOAuth::exchange_code(code, verifier).await?;
+    let account = SpotifyManager::link_account(&mut redis, &token).await?;
+    Ok(HttpResponse::Ok()
+        .insert_header(("Content-Type", "application/json"))
+        .json(json!({ "success": true, "data": account })))
+}
+
+// Human-readable page for a failed authorization, since the user lands here from the browser
+fn auth_error_page(status: StatusCode, message: &str) -> HttpResponse {
+    let body = format!(
+        "<!DOCTYPE html>\n<html>\n<head><meta charset=\"utf-8\"><title>Spotify authorization failed</title></head>\n<body>\n<h1>Spotify authorization failed</h1>\n<p>{}</p>\n<p><a href=\"/v1/spotify/auth\">Try again</a></p>\n</body>\n</html>\n",
+        escape_html(message)
+    );
+    HttpResponse::build(status)
+        .content_type("text/html; charset=utf-8")
+        .body(body)
+}
+
+fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
 }
 
 // Enhanced real-time song information endpoints
+#[routes]
 #[get("/v1/spotify/realtime")]
-async fn realtime_info(data: web::Data<Mutex<NullClient>>) -> Result<HttpResponse, AppError> {
+#[get("/v1/spotify/{account}/realtime")]
+async fn realtime_info(
+    path: web::Path<AccountPath>,
+    data: web::Data<Mutex<NullClient>>,
+) -> Result<HttpResponse, AppError> {
     let data = data.lock().await;
     let mut redis = data.redis.clone();
     drop(data);
+    let account = SpotifyManager::resolve_account(&mut redis, path.account.as_deref()).await?;
 
-    let song_info = SpotifyManager::get_real_time_song_info(&mut redis).await?;
+    let song_info = SpotifyManager::get_real_time_song_info(&mut redis, &account).await?;
     Ok(HttpResponse::Ok()
         .insert_header(("Content-Type", "application/json"))
         .json(json!({
@@ -113,13 +134,56 @@ async fn realtime_info(data: web::Data<Mutex<NullClient>>) -> Result<HttpRespons
         })))
 }
 
+// Playback updates pushed as Server-Sent Events
+#[routes]
+#[get("/v1/spotify/stream")]
+#[get("/v1/spotify/{account}/stream")]
+async fn stream(
+    path: web::Path<AccountPath>,
+    data: web::Data<Mutex<NullClient>>,
+    playback: web::Data<PlaybackHub>,
+) -> Result<HttpResponse, AppError> {
+    let mut redis = data.lock().await.redis.clone();
+    let account = SpotifyManager::resolve_account(&mut redis, path.account.as_deref()).await?;
+
+    Ok(HttpResponse::Ok()
+        .insert_header(("Content-Type", "text/event-stream"))
+        .insert_header(("Cache-Control", "no-cache"))
+        .streaming(playback.get(&account).sse(&data)))
+}
+
+// Playback updates pushed over a WebSocket
+#[routes]
+#[get("/v1/spotify/stream/ws")]
+#[get("/v1/spotify/{account}/stream/ws")]
+async fn stream_ws(
+    req: HttpRequest,
+    body: web::Payload,
+    path: web::Path<AccountPath>,
+    data: web::Data<Mutex<NullClient>>,
+    playback: web::Data<PlaybackHub>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let mut redis = data.lock().await.redis.clone();
+    let account = SpotifyManager::resolve_account(&mut redis, path.account.as_deref()).await?;
+
+    let (response, session, messages) = actix_ws::handle(&req, body)?;
+    actix_web::rt::spawn(playback.get(&account).websocket(data, session, messages));
+    Ok(response)
+}
+
+#[routes]
 #[get("/v1/spotify/devices")]
-async fn devices(data: web::Data<Mutex<NullClient>>) -> Result<HttpResponse, AppError> {
+#[get("/v1/spotify/{account}/devices")]
+async fn devices(
+    path: web::Path<AccountPath>,
+    data: web::Data<Mutex<NullClient>>,
+) -> Result<HttpResponse, AppError> {
     let data = data.lock().await;
     let mut redis = data.redis.clone();
     drop(data);
+    let account = SpotifyManager::resolve_account(&mut redis, path.account.as_deref()).await?;
 
-    let devices = SpotifyManager::get_devices(&mut redis).await?;
+    let devices = SpotifyManager::get_devices(&mut redis, &account).await?;
     Ok(HttpResponse::Ok()
         .insert_header(("Content-Type", "application/json"))
         .json(json!({
@@ -129,13 +193,19 @@ async fn devices(data: web::Data<Mutex<NullClient>>) -> Result<HttpResponse, App
         })))
 }
 
+#[routes]
 #[get("/v1/spotify/queue")]
-async fn queue(data: web::Data<Mutex<NullClient>>) -> Result<HttpResponse, AppError> {
+#[get("/v1/spotify/{account}/queue")]
+async fn queue(
+    path: web::Path<AccountPath>,
+    data: web::Data<Mutex<NullClient>>,
+) -> Result<HttpResponse, AppError> {
     let data = data.lock().await;
     let mut redis = data.redis.clone();
     drop(data);
+    let account = SpotifyManager::resolve_account(&mut redis, path.account.as_deref()).await?;
 
-    let queue_info = SpotifyManager::get_queue(&mut redis).await?;
+    let queue_info = SpotifyManager::get_queue(&mut redis, &account).await?;
     Ok(HttpResponse::Ok()
         .insert_header(("Content-Type", "application/json"))
         .json(json!({
@@ -145,16 +215,20 @@ async fn queue(data: web::Data<Mutex<NullClient>>) -> Result<HttpResponse, AppEr
 }
 
 // Dashboard analytics endpoints
+#[routes]
 #[get("/v1/spotify/top/tracks")]
+#[get("/v1/spotify/{account}/top/tracks")]
 async fn top_tracks(
+    path: web::Path<AccountPath>,
     data: web::Data<Mutex<NullClient>>,
     query: web::Query<TopTracksQuery>
 ) -> Result<HttpResponse, AppError> {
     let data = data.lock().await;
     let mut redis = data.redis.clone();
     drop(data);
+    let account = SpotifyManager::resolve_account(&mut redis, path.account.as_deref()).await?;
 
-    let tracks_data = SpotifyManager::get_top_tracks(&mut redis, &query.time_range, query.limit).await?;
+    let tracks_data = SpotifyManager::get_top_tracks(&mut redis, &account, &query.time_range, query.limit).await?;
     Ok(HttpResponse::Ok()
         .insert_header(("Content-Type", "application/json"))
         .json(json!({
@@ -165,16 +239,20 @@ async fn top_tracks(
         })))
 }
 
+#[routes]
 #[get("/v1/spotify/top/artists")]
+#[get("/v1/spotify/{account}/top/artists")]
 async fn top_artists(
+    path: web::Path<AccountPath>,
     data: web::Data<Mutex<NullClient>>,
     query: web::Query<TopArtistsQuery>
 ) -> Result<HttpResponse, AppError> {
     let data = data.lock().await;
     let mut redis = data.redis.clone();
     drop(data);
+    let account = SpotifyManager::resolve_account(&mut redis, path.account.as_deref()).await?;
 
-    let artists_data = SpotifyManager::get_top_artists(&mut redis, &query.time_range, query.limit).await?;
+    let artists_data = SpotifyManager::get_top_artists(&mut redis, &account, &query.time_range, query.limit).await?;
     Ok(HttpResponse::Ok()
         .insert_header(("Content-Type", "application/json"))
         .json(json!({
@@ -185,16 +263,29 @@ async fn top_artists(
         })))
 }
 
+#[routes]
 #[get("/v1/spotify/recently-played")]
+#[get("/v1/spotify/{account}/recently-played")]
 async fn recently_played(
+    path: web::Path<AccountPath>,
     data: web::Data<Mutex<NullClient>>,
     query: web::Query<RecentlyPlayedQuery>
 ) -> Result<HttpResponse, AppError> {
     let data = data.lock().await;
     let mut redis = data.redis.clone();
+    let pool = data.postgres.pool.clone();
     drop(data);
+    let account = SpotifyManager::resolve_account(&mut redis, path.account.as_deref()).await?;
 
-    let recent_data = SpotifyManager::get_recently_played(&mut redis, query.limit).await?;
+    let recent_data = SpotifyManager::get_recently_played(&mut redis, &account, query.limit).await?;
+    match serde_json::from_value::<RecentlyPlayed>(recent_data.clone()) {
+        Ok(recent) => {
+            if let Err(e) = SpotifyRepository::record_plays(&pool, &account, &recent.items).await {
+                error!("Failed to record Spotify plays: {}", e);
+            }
+        }
+        Err(e) => error!("Failed to parse recently played tracks: {}", e),
+    }
     Ok(HttpResponse::Ok()
         .insert_header(("Content-Type", "application/json"))
         .json(json!({
@@ -204,16 +295,20 @@ async fn recently_played(
         })))
 }
 
+#[routes]
 #[get("/v1/spotify/playlists")]
+#[get("/v1/spotify/{account}/playlists")]
 async fn playlists(
+    path: web::Path<AccountPath>,
     data: web::Data<Mutex<NullClient>>,
     query: web::Query<PlaylistsQuery>
 ) -> Result<HttpResponse, AppError> {
     let data = data.lock().await;
     let mut redis = data.redis.clone();
     drop(data);
+    let account = SpotifyManager::resolve_account(&mut redis, path.account.as_deref()).await?;
 
-    let playlists_data = SpotifyManager::get_user_playlists(&mut redis, query.limit).await?;
+    let playlists_data = SpotifyManager::get_user_playlists(&mut redis, &account, query.limit).await?;
     Ok(HttpResponse::Ok()
         .insert_header(("Content-Type", "application/json"))
         .json(json!({
@@ -223,6 +318,285 @@ async fn playlists(
         })))
 }
 
+// Stored listening history, newest first
+#[routes]
+#[get("/v1/spotify/history")]
+#[get("/v1/spotify/{account}/history")]
+async fn history(
+    path: web::Path<AccountPath>,
+    data: web::Data<Mutex<NullClient>>,
+    query: web::Query<HistoryQuery>,
+) -> Result<HttpResponse, AppError> {
+    let data = data.lock().await;
+    let mut redis = data.redis.clone();
+    let pool = data.postgres.pool.clone();
+    drop(data);
+    let account = SpotifyManager::resolve_account(&mut redis, path.account.as_deref()).await?;
+
+    let limit = query.limit.clamp(1, 200);
+    let before = query.cursor.and_then(DateTime::<Utc>::from_timestamp_millis);
+    let plays = SpotifyRepository::history(&pool, &account, query.from, query.to, before, limit).await?;
+    let next_cursor = if plays.len() as i64 == limit {
+        plays.last().map(|play| play.played_at.timestamp_millis())
+    } else {
+        None
+    };
+
+    Ok(HttpResponse::Ok()
+        .insert_header(("Content-Type", "application/json"))
+        .json(json!({
+            "success": true,
+            "data": plays,
+            "count": plays.len(),
+            "next_cursor": next_cursor
+        })))
+}
+
+// Listening analytics computed from the stored history
+#[routes]
+#[get("/v1/spotify/stats")]
+#[get("/v1/spotify/{account}/stats")]
+async fn stats(
+    path: web::Path<AccountPath>,
+    data: web::Data<Mutex<NullClient>>,
+    query: web::Query<StatsQuery>,
+) -> Result<HttpResponse, AppError> {
+    let data = data.lock().await;
+    let mut redis = data.redis.clone();
+    let pool = data.postgres.pool.clone();
+    drop(data);
+    let account = SpotifyManager::resolve_account(&mut redis, path.account.as_deref()).await?;
+
+    // Postgres rejects unknown zones with a database error, so catch them here
+    if query.tz.parse::<chrono_tz::Tz>().is_err() {
+        return Err(AppError::BadRequest(format!(
+            "Unknown time zone '{}', expected an IANA name like Europe/Berlin",
+            query.tz
+        )));
+    }
+
+    let limit = query.limit.clamp(1, 50);
+    let stats = SpotifyRepository::stats(&pool, &account, query.from, query.to, &query.tz, limit).await?;
+    Ok(HttpResponse::Ok()
+        .insert_header(("Content-Type", "application/json"))
+        .json(json!({
+            "success": true,
+            "data": stats
+        })))
+}
+
+// Linked account management
+#[get("/v1/spotify/accounts")]
+async fn accounts(
+    _admin: Admin,
+    data: web::Data<Mutex<NullClient>>,
+) -> Result<HttpResponse, AppError> {
+    let mut redis = data.lock().await.redis.clone();
+    let accounts = SpotifyManager::list_accounts(&mut redis).await?;
+    Ok(HttpResponse::Ok()
+        .insert_header(("Content-Type", "application/json"))
+        .json(json!({
+            "success": true,
+            "data": accounts,
+            "count": accounts.len()
+        })))
+}
+
+#[delete("/v1/spotify/accounts/{account}")]
+async fn revoke_account(
+    _admin: Admin,
+    path: web::Path<String>,
+    data: web::Data<Mutex<NullClient>>,
+) -> Result<HttpResponse, AppError> {
+    let mut redis = data.lock().await.redis.clone();
+    let account = path.into_inner();
+    if !SpotifyManager::revoke_account(&mut redis, &account).await? {
+        return Err(AppError::NotFound(format!("Spotify account {} is not linked", account)));
+    }
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[put("/v1/spotify/accounts/{account}/default")]
+async fn default_account(
+    _admin: Admin,
+    path: web::Path<String>,
+    data: web::Data<Mutex<NullClient>>,
+) -> Result<HttpResponse, AppError> {
+    let mut redis = data.lock().await.redis.clone();
+    SpotifyManager::set_default_account(&mut redis, &path).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+// Playback control endpoints, gated behind the admin key
+#[routes]
+#[post("/v1/spotify/player/play")]
+#[post("/v1/spotify/{account}/player/play")]
+async fn player_play(
+    _admin: Admin,
+    path: web::Path<AccountPath>,
+    data: web::Data<Mutex<NullClient>>,
+    query: web::Query<DeviceQuery>,
+    body: Option<web::Json<PlayRequest>>,
+) -> Result<HttpResponse, AppError> {
+    let mut redis = data.lock().await.redis.clone();
+    let account = SpotifyManager::resolve_account(&mut redis, path.account.as_deref()).await?;
+    SpotifyManager::play(&mut redis, &account, &query.device_id, body.as_deref()).await?;
+    Ok(player_ok("play"))
+}
+
+#[routes]
+#[post("/v1/spotify/player/pause")]
+#[post("/v1/spotify/{account}/player/pause")]
+async fn player_pause(
+    _admin: Admin,
+    path: web::Path<AccountPath>,
+    data: web::Data<Mutex<NullClient>>,
+    query: web::Query<DeviceQuery>,
+) -> Result<HttpResponse, AppError> {
+    let mut redis = data.lock().await.redis.clone();
+    let account = SpotifyManager::resolve_account(&mut redis, path.account.as_deref()).await?;
+    SpotifyManager::pause(&mut redis, &account, &query.device_id).await?;
+    Ok(player_ok("pause"))
+}
+
+#[routes]
+#[post("/v1/spotify/player/next")]
+#[post("/v1/spotify/{account}/player/next")]
+async fn player_next(
+    _admin: Admin,
+    path: web::Path<AccountPath>,
+    data: web::Data<Mutex<NullClient>>,
+    query: web::Query<DeviceQuery>,
+) -> Result<HttpResponse, AppError> {
+    let mut redis = data.lock().await.redis.clone();
+    let account = SpotifyManager::resolve_account(&mut redis, path.account.as_deref()).await?;
+    SpotifyManager::next(&mut redis, &account, &query.device_id).await?;
+    Ok(player_ok("next"))
+}
+
+#[routes]
+#[post("/v1/spotify/player/previous")]
+#[post("/v1/spotify/{account}/player/previous")]
+async fn player_previous(
+    _admin: Admin,
+    path: web::Path<AccountPath>,
+    data: web::Data<Mutex<NullClient>>,
+    query: web::Query<DeviceQuery>,
+) -> Result<HttpResponse, AppError> {
+    let mut redis = data.lock().await.redis.clone();
+    let account = SpotifyManager::resolve_account(&mut redis, path.account.as_deref()).await?;
+    SpotifyManager::previous(&mut redis, &account, &query.device_id).await?;
+    Ok(player_ok("previous"))
+}
+
+#[routes]
+#[put("/v1/spotify/player/seek")]
+#[put("/v1/spotify/{account}/player/seek")]
+async fn player_seek(
+    _admin: Admin,
+    path: web::Path<AccountPath>,
+    data: web::Data<Mutex<NullClient>>,
+    query: web::Query<SeekQuery>,
+) -> Result<HttpResponse, AppError> {
+    let mut redis = data.lock().await.redis.clone();
+    let account = SpotifyManager::resolve_account(&mut redis, path.account.as_deref()).await?;
+    SpotifyManager::seek(&mut redis, &account, query.position_ms, &query.device_id).await?;
+    Ok(player_ok("seek"))
+}
+
+#[routes]
+#[put("/v1/spotify/player/volume")]
+#[put("/v1/spotify/{account}/player/volume")]
+async fn player_volume(
+    _admin: Admin,
+    path: web::Path<AccountPath>,
+    data: web::Data<Mutex<NullClient>>,
+    query: web::Query<VolumeQuery>,
+) -> Result<HttpResponse, AppError> {
+    if query.volume_percent > 100 {
+        return Err(AppError::BadRequest("volume_percent must be between 0 and 100".to_string()));
+    }
+    let mut redis = data.lock().await.redis.clone();
+    let account = SpotifyManager::resolve_account(&mut redis, path.account.as_deref()).await?;
+    SpotifyManager::set_volume(&mut redis, &account, query.volume_percent, &query.device_id).await?;
+    Ok(player_ok("volume"))
+}
+
+#[routes]
+#[put("/v1/spotify/player/shuffle")]
+#[put("/v1/spotify/{account}/player/shuffle")]
+async fn player_shuffle(
+    _admin: Admin,
+    path: web::Path<AccountPath>,
+    data: web::Data<Mutex<NullClient>>,
+    query: web::Query<ShuffleQuery>,
+) -> Result<HttpResponse, AppError> {
+    let mut redis = data.lock().await.redis.clone();
+    let account = SpotifyManager::resolve_account(&mut redis, path.account.as_deref()).await?;
+    SpotifyManager::set_shuffle(&mut redis, &account, query.state, &query.device_id).await?;
+    Ok(player_ok("shuffle"))
+}
+
+#[routes]
+#[put("/v1/spotify/player/repeat")]
+#[put("/v1/spotify/{account}/player/repeat")]
+async fn player_repeat(
+    _admin: Admin,
+    path: web::Path<AccountPath>,
+    data: web::Data<Mutex<NullClient>>,
+    query: web::Query<RepeatQuery>,
+) -> Result<HttpResponse, AppError> {
+    if !["track", "context", "off"].contains(&query.state.as_str()) {
+        return Err(AppError::BadRequest("state must be one of track, context or off".to_string()));
+    }
+    let mut redis = data.lock().await.redis.clone();
+    let account = SpotifyManager::resolve_account(&mut redis, path.account.as_deref()).await?;
+    SpotifyManager::set_repeat(&mut redis, &account, &query.state, &query.device_id).await?;
+    Ok(player_ok("repeat"))
+}
+
+#[routes]
+#[put("/v1/spotify/player/transfer")]
+#[put("/v1/spotify/{account}/player/transfer")]
+async fn player_transfer(
+    _admin: Admin,
+    path: web::Path<AccountPath>,
+    data: web::Data<Mutex<NullClient>>,
+    body: web::Json<TransferRequest>,
+) -> Result<HttpResponse, AppError> {
+    let mut redis = data.lock().await.redis.clone();
+    let account = SpotifyManager::resolve_account(&mut redis, path.account.as_deref()).await?;
+    SpotifyManager::transfer_playback(&mut redis, &account, &body).await?;
+    Ok(player_ok("transfer"))
+}
+
+#[routes]
+#[post("/v1/spotify/player/queue")]
+#[post("/v1/spotify/{account}/player/queue")]
+async fn player_queue(
+    _admin: Admin,
+    path: web::Path<AccountPath>,
+    data: web::Data<Mutex<NullClient>>,
+    query: web::Query<QueueQuery>,
+) -> Result<HttpResponse, AppError> {
+    let mut redis = data.lock().await.redis.clone();
+    let account = SpotifyManager::resolve_account(&mut redis, path.account.as_deref()).await?;
+    SpotifyManager::add_to_queue(&mut redis, &account, &query.uri, &query.device_id).await?;
+    Ok(player_ok("queue"))
+}
+
+fn player_ok(action: &str) -> HttpResponse {
+    HttpResponse::Ok()
+        .insert_header(("Content-Type", "application/json"))
+        .json(json!({ "success": true, "action": action }))
+}
+
+// Account named in the path; unscoped routes use the default account
+#[derive(serde::Deserialize)]
+pub struct AccountPath {
+    pub account: Option<String>,
+}
+
 // Query parameter structures for dashboard endpoints
 #[derive(serde::Deserialize)]
 pub struct TopTracksQuery {
@@ -252,6 +626,61 @@ pub struct PlaylistsQuery {
     pub limit: i32,
 }
 
+#[derive(serde::Deserialize)]
+pub struct HistoryQuery {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    #[serde(default = "default_history_limit")]
+    pub limit: i64,
+    pub cursor: Option<i64>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct StatsQuery {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    #[serde(default = "default_timezone")]
+    pub tz: String,
+    #[serde(default = "default_stats_limit")]
+    pub limit: i64,
+}
+
+// Query parameter structures for playback control
+#[derive(serde::Deserialize)]
+pub struct DeviceQuery {
+    pub device_id: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct SeekQuery {
+    pub position_ms: u32,
+    pub device_id: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct VolumeQuery {
+    pub volume_percent: u8,
+    pub device_id: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct ShuffleQuery {
+    pub state: bool,
+    pub device_id: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct RepeatQuery {
+    pub state: String,
+    pub device_id: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct QueueQuery {
+    pub uri: String,
+    pub device_id: Option<String>,
+}
+
 // Default values for query parameters
 fn default_time_range() -> String {
     "medium_term".to_string()
@@ -261,3 +690,14 @@ fn default_limit() -> i32 {
     20
 }
 
+fn default_history_limit() -> i64 {
+    50
+}
+
+fn default_timezone() -> String {
+    "UTC".to_string()
+}
+
+fn default_stats_limit() -> i64 {
+    10
+}
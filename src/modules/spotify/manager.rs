@@ -1,30 +1,44 @@
-use envconfig::Envconfig;
 use log::info;
 use redis::{aio::ConnectionManager, AsyncCommands};
-use reqwest::header::{ACCEPT, AUTHORIZATION, CONTENT_TYPE};
+use reqwest::{header::CONTENT_LENGTH, Method};
 
-use crate::{config::Config, db::redis::RedisManager, error::AppError};
+use crate::{db::redis::RedisManager, error::{AppError, SpotifyError}};
 
-use super::entity::{
-    DeviceInfo, PlayerState, QueueInfo, RealTimeSongInfo, SpotifyToken, TokenResponse,
+use super::{
+    api::{account_key, SpotifyApi},
+    entity::{
+        ArtistGenres, DeviceInfo, PlayRequest, Playback, PlayerState, QueueInfo, RealTimeSongInfo,
+        RecentlyPlayed, Root, SpotifyAccount, SpotifyProfile, SpotifyToken, TransferRequest,
+    },
 };
 
 #[derive(Clone)]
 pub struct SpotifyManager;
 
 impl SpotifyManager {
-    pub async fn store_spotify_creds(redis: &mut RedisManager, data: &SpotifyToken) -> Result<(), AppError> {
-        redis::cmd("SET")
-            .arg("spotify:access_token")
-            .arg(&data.access_token)
-            .arg("EX")
-            .arg(data.expires_in)
-            .query_async::<ConnectionManager, String>(&mut redis.connection)
+    // Link the account that owns `token`, keyed by its Spotify user id from `/me`
+    pub async fn link_account(redis: &mut RedisManager, token: &SpotifyToken) -> Result<SpotifyAccount, AppError> {
+        let response = SpotifyApi::request(Method::GET, "https://api.spotify.com/v1/me", &token.access_token)
+            .send()
             .await?;
+        if !response.status().is_success() {
+            return Err(SpotifyError::Other(format!(
+                "Failed to fetch profile: {}",
+                response.status()
+            )).into());
+        }
+        let profile: SpotifyProfile = response.json().await?;
+
+        Self::store_spotify_creds(redis, &profile.id, token).await?;
+        Self::register_account(redis, &profile).await
+    }
+
+    pub async fn store_spotify_creds(redis: &mut RedisManager, account: &str, data: &SpotifyToken) -> Result<(), AppError> {
+        SpotifyApi::store_access_token(redis, account, &data.access_token, i64::from(data.expires_in)).await?;
 
         if let Some(refresh_token) = &data.refresh_token {
             redis::cmd("SET")
-                .arg("spotify:refresh_token")
+                .arg(account_key(account, "refresh_token"))
                 .arg(refresh_token)
                 .query_async::<ConnectionManager, String>(&mut redis.connection)
                 .await?;
@@ -33,121 +47,176 @@ impl SpotifyManager {
         Ok(())
     }
 
-    pub async fn renew_spotify_access(redis: &mut RedisManager) -> Result<bool, AppError> {
-        let exists: bool = redis.connection.exists("spotify:refresh_token").await?;
-        Ok(exists)
+    async fn register_account(redis: &mut RedisManager, profile: &SpotifyProfile) -> Result<SpotifyAccount, AppError> {
+        redis.connection.sadd::<_, _, ()>("spotify:accounts", &profile.id).await?;
+        redis
+            .connection
+            .set::<_, _, ()>(account_key(&profile.id, "profile"), serde_json::to_string(profile)?)
+            .await?;
+        let first: bool = redis.connection.set_nx("spotify:default_account", &profile.id).await?;
+        info!("Linked Spotify account {}", profile.id);
+
+        Ok(SpotifyAccount {
+            id: profile.id.clone(),
+            display_name: profile.display_name.clone(),
+            default: first || Self::default_account(redis).await? == Some(profile.id.clone()),
+        })
     }
 
-    pub async fn check_spotify_auth(redis: &mut RedisManager) -> Result<bool, AppError> {
-        let exists: bool = redis.connection.exists("spotify:refresh_token").await?;
-        Ok(exists)
+    // Every linked account id
+    pub async fn accounts(redis: &mut RedisManager) -> Result<Vec<String>, AppError> {
+        let mut accounts: Vec<String> = redis.connection.smembers("spotify:accounts").await?;
+        accounts.sort();
+        Ok(accounts)
     }
 
-    pub async fn check_spotify_access(redis: &mut RedisManager) -> Result<bool, AppError> {
-        let exists: bool = redis.connection.exists("spotify:access_token").await?;
-        Ok(exists)
+    pub async fn list_accounts(redis: &mut RedisManager) -> Result<Vec<SpotifyAccount>, AppError> {
+        let default = Self::default_account(redis).await?;
+        let mut linked = Vec::new();
+        for id in Self::accounts(redis).await? {
+            let profile: Option<String> = redis.connection.get(account_key(&id, "profile")).await?;
+            let display_name = profile
+                .and_then(|profile| serde_json::from_str::<SpotifyProfile>(&profile).ok())
+                .and_then(|profile| profile.display_name);
+            linked.push(SpotifyAccount {
+                default: default.as_deref() == Some(id.as_str()),
+                id,
+                display_name,
+            });
+        }
+        Ok(linked)
     }
 
-    pub async fn get_spotify_current(redis: &mut RedisManager) -> Result<(), AppError> {
-        if Self::check_spotify_access(redis).await? {
-            let token: String = redis.connection.get("spotify:access_token").await?;
+    // Forget an account's credentials, moving the default to another account if needed
+    pub async fn revoke_account(redis: &mut RedisManager, account: &str) -> Result<bool, AppError> {
+        let removed: bool = redis.connection.srem("spotify:accounts", account).await?;
+        if !removed {
+            return Ok(false);
+        }
 
-            let response = reqwest::Client::new()
-                .get("https://api.spotify.com/v1/me/player/currently-playing")
-                .header(AUTHORIZATION, format!("Bearer {}", token))
-                .header(CONTENT_TYPE, "application/json")
-                .header(ACCEPT, "application/json")
-                .send()
-                .await?;
-                
-            let status = response.status().as_u16();
-            let content_length = response.content_length().unwrap_or(0);
-
-            match status {
-                200 => {
-                    info!("Currently playing");
-                    if content_length > 0 {
-                        let text = response.text().await?;
-                        redis::cmd("SET")
-                            .arg("spotify:now_playing")
-                            .arg(text)
-                            .arg("EX")
-                            .arg(300) // 5 minutes TTL
-                            .query_async::<ConnectionManager, String>(&mut redis.connection)
-                            .await?;
-                    }
-                }
-                204 => {
-                    info!("Nothing currently playing");
-                }
-                401 => {
-                    info!("Access token expired, refreshing");
-                    let token_response = Self::refresh_access_token(redis).await?;
-
-                    redis::cmd("SET")
-                        .arg("spotify:access_token")
-                        .arg(token_response.access_token)
-                        .arg("EX")
-                        .arg(token_response.expires_in)
-                        .query_async::<ConnectionManager, String>(&mut redis.connection)
-                        .await?;
-                }
-                429 => {
-                    info!("Rate limit exceeded");
-                }
-                _ => {
-                    info!("Unexpected status code: {}", status);
-                }
+        for name in ["access_token", "refresh_token", "profile", "now_playing", "realtime_info"] {
+            redis.connection.del::<_, ()>(account_key(account, name)).await?;
+        }
+        if Self::default_account(redis).await?.as_deref() == Some(account) {
+            match Self::accounts(redis).await?.first() {
+                Some(next) => redis.connection.set::<_, _, ()>("spotify:default_account", next).await?,
+                None => redis.connection.del::<_, ()>("spotify:default_account").await?,
             }
         }
+
+        info!("Revoked Spotify account {}", account);
+        Ok(true)
+    }
+
+    pub async fn set_default_account(redis: &mut RedisManager, account: &str) -> Result<(), AppError> {
+        let account = Self::resolve_account(redis, Some(account)).await?;
+        redis.connection.set::<_, _, ()>("spotify:default_account", account).await?;
         Ok(())
     }
 
-    async fn refresh_access_token(
-        redis: &mut RedisManager,
-    ) -> Result<TokenResponse, AppError> {
-        let config = Config::init_from_env()?;
-        let token: String = redis.connection.get("spotify:refresh_token").await?;
-        
-        let params = [
-            ("grant_type", "refresh_token"),
-            ("refresh_token", token.as_str()),
-            ("client_id", config.spotify_client_id.as_str()),
-            ("client_secret", config.spotify_client_secret.as_str()),
-        ];
-
-        let response = reqwest::Client::new()
-            .post("https://accounts.spotify.com/api/token")
-            .form(&params)
-            .send()
+    pub async fn default_account(redis: &mut RedisManager) -> Result<Option<String>, AppError> {
+        let account: Option<String> = redis.connection.get("spotify:default_account").await?;
+        Ok(account)
+    }
+
+    // The requested account if it is linked, otherwise the default account
+    pub async fn resolve_account(redis: &mut RedisManager, account: Option<&str>) -> Result<String, AppError> {
+        if let Some(account) = account {
+            let linked: bool = redis.connection.sismember("spotify:accounts", account).await?;
+            return if linked {
+                Ok(account.to_string())
+            } else {
+                Err(AppError::NotFound(format!("Spotify account {} is not linked", account)))
+            };
+        }
+
+        if let Some(account) = Self::default_account(redis).await? {
+            return Ok(account);
+        }
+        match Self::migrate_legacy_account(redis).await? {
+            Some(account) => Ok(account),
+            None => Err(SpotifyError::Other("Not authenticated".to_string()).into()),
+        }
+    }
+
+    // Move credentials stored under the old global keys to their owning account
+    async fn migrate_legacy_account(redis: &mut RedisManager) -> Result<Option<String>, AppError> {
+        let names = ["refresh_token", "access_token"];
+        for name in names {
+            let key = format!("spotify:{}", name);
+            if redis.connection.exists(&key).await? {
+                redis.connection.rename::<_, _, ()>(&key, account_key("legacy", name)).await?;
+            }
+        }
+
+        // Also picks up a migration that failed after the rename
+        let pending: bool = redis.connection.exists(account_key("legacy", "refresh_token")).await?;
+        if !pending {
+            return Ok(None);
+        }
+
+        let profile: SpotifyProfile = SpotifyApi::get_json(redis, "legacy", "https://api.spotify.com/v1/me").await?;
+        for name in names {
+            let key = account_key("legacy", name);
+            if redis.connection.exists(&key).await? {
+                redis.connection.rename::<_, _, ()>(&key, account_key(&profile.id, name)).await?;
+            }
+        }
+
+        info!("Migrated legacy Spotify credentials to account {}", profile.id);
+        Ok(Some(Self::register_account(redis, &profile).await?.id))
+    }
+
+    pub async fn check_spotify_auth(redis: &mut RedisManager) -> Result<bool, AppError> {
+        let linked: bool = redis.connection.exists("spotify:default_account").await?;
+        let legacy: bool = redis
+            .connection
+            .exists(&["spotify:refresh_token", "spotify:legacy:refresh_token"])
             .await?;
-            
-        let response_text = response.text().await?;
-        let token_response: TokenResponse = serde_json::from_str(&response_text)?;
-        Ok(token_response)
+        Ok(linked || legacy)
+    }
+
+    // Currently playing track, `None` when nothing is playing
+    pub async fn get_spotify_current(redis: &mut RedisManager, account: &str) -> Result<Option<Root>, AppError> {
+        let response =
+            SpotifyApi::get(redis, account, "https://api.spotify.com/v1/me/player/currently-playing").await?;
+
+        if response.status() == 204 {
+            info!("Nothing currently playing");
+            return Ok(None);
+        }
+
+        let text = SpotifyApi::check(response).await?.text().await?;
+        let playing: Root = serde_json::from_str(&text)?;
+        redis::cmd("SET")
+            .arg(account_key(account, "now_playing"))
+            .arg(text)
+            .arg("EX")
+            .arg(300) // 5 minutes TTL
+            .query_async::<ConnectionManager, String>(&mut redis.connection)
+            .await?;
+        Ok(Some(playing))
     }
 
     // Enhanced real-time song information methods
-    pub async fn get_real_time_song_info(redis: &mut RedisManager) -> Result<RealTimeSongInfo, AppError> {
-        if !Self::check_spotify_access(redis).await? {
-            return Err(AppError::SpotifyError("Not authenticated".to_string()));
+    pub async fn get_real_time_song_info(redis: &mut RedisManager, account: &str) -> Result<RealTimeSongInfo, AppError> {
+        match Self::get_playback(redis, account).await? {
+            Playback::Playing(info) => Ok(*info),
+            Playback::NoDevice => Err(SpotifyError::Other("No active device".to_string()).into()),
+            Playback::NoTrack => Err(SpotifyError::Other("No track playing".to_string()).into()),
         }
+    }
 
-        let token: String = redis.connection.get("spotify:access_token").await?;
-        
+    // Current playback, where nothing playing is a normal state rather than an error
+    pub async fn get_playback(redis: &mut RedisManager, account: &str) -> Result<Playback, AppError> {
         // Get player state
-        let player_response = reqwest::Client::new()
-            .get("https://api.spotify.com/v1/me/player")
-            .header(AUTHORIZATION, format!("Bearer {}", token))
-            .header(ACCEPT, "application/json")
-            .send()
-            .await?;
+        let player_response = SpotifyApi::get(redis, account, "https://api.spotify.com/v1/me/player").await?;
 
         if player_response.status() == 204 {
-            return Err(AppError::SpotifyError("No active device".to_string()));
+            return Ok(Playback::NoDevice);
         }
 
-        let player_state: PlayerState = player_response.json().await?;
+        let player_state: PlayerState = SpotifyApi::check(player_response).await?.json().await?;
         
         if let Some(item) = player_state.item {
             let album_images = item.album.images.clone();
@@ -160,7 +229,7 @@ impl SpotifyManager {
                     duration_ms: item.duration_ms,
                     explicit: item.explicit,
                     popularity: item.popularity,
-                    preview_url: Some(item.preview_url),
+                    preview_url: item.preview_url,
                     external_urls: item.external_urls,
                     images: album_images,
                 },
@@ -180,152 +249,183 @@ impl SpotifyManager {
             // Cache the real-time info
             let cache_data = serde_json::to_string(&track_info)?;
             redis::cmd("SET")
-                .arg("spotify:realtime_info")
+                .arg(account_key(account, "realtime_info"))
                 .arg(cache_data)
                 .arg("EX")
                 .arg(30) // 30 seconds TTL for real-time data
                 .query_async::<ConnectionManager, String>(&mut redis.connection)
                 .await?;
 
-            Ok(track_info)
+            Ok(Playback::Playing(Box::new(track_info)))
         } else {
-            Err(AppError::SpotifyError("No track playing".to_string()))
+            Ok(Playback::NoTrack)
         }
     }
 
     // Get available devices
-    pub async fn get_devices(redis: &mut RedisManager) -> Result<Vec<DeviceInfo>, AppError> {
-        if !Self::check_spotify_access(redis).await? {
-            return Err(AppError::SpotifyError("Not authenticated".to_string()));
-        }
-
-        let token: String = redis.connection.get("spotify:access_token").await?;
-        
-        let response = reqwest::Client::new()
-            .get("https://api.spotify.com/v1/me/player/devices")
-            .header(AUTHORIZATION, format!("Bearer {}", token))
-            .header(ACCEPT, "application/json")
-            .send()
-            .await?;
-
-        let devices_response: serde_json::Value = response.json().await?;
+    pub async fn get_devices(redis: &mut RedisManager, account: &str) -> Result<Vec<DeviceInfo>, AppError> {
+        let devices_response: serde_json::Value =
+            SpotifyApi::get_json(redis, account, "https://api.spotify.com/v1/me/player/devices").await?;
         let devices: Vec<DeviceInfo> = serde_json::from_value(devices_response["devices"].clone())?;
         
         Ok(devices)
     }
 
     // Get current queue
-    pub async fn get_queue(redis: &mut RedisManager) -> Result<QueueInfo, AppError> {
-        if !Self::check_spotify_access(redis).await? {
-            return Err(AppError::SpotifyError("Not authenticated".to_string()));
-        }
-
-        let token: String = redis.connection.get("spotify:access_token").await?;
-        
-        let response = reqwest::Client::new()
-            .get("https://api.spotify.com/v1/me/player/queue")
-            .header(AUTHORIZATION, format!("Bearer {}", token))
-            .header(ACCEPT, "application/json")
-            .send()
-            .await?;
-
-        let queue_info: QueueInfo = response.json().await?;
+    pub async fn get_queue(redis: &mut RedisManager, account: &str) -> Result<QueueInfo, AppError> {
+        let queue_info: QueueInfo =
+            SpotifyApi::get_json(redis, account, "https://api.spotify.com/v1/me/player/queue").await?;
         Ok(queue_info)
     }
 
     // Get user's top tracks (for dashboard analytics)
-    pub async fn get_top_tracks(redis: &mut RedisManager, time_range: &str, limit: i32) -> Result<serde_json::Value, AppError> {
-        if !Self::check_spotify_access(redis).await? {
-            return Err(AppError::SpotifyError("Not authenticated".to_string()));
-        }
-
-        let token: String = redis.connection.get("spotify:access_token").await?;
-        
+    pub async fn get_top_tracks(redis: &mut RedisManager, account: &str, time_range: &str, limit: i32) -> Result<serde_json::Value, AppError> {
         let url = format!(
             "https://api.spotify.com/v1/me/top/tracks?time_range={}&limit={}&offset=0",
             time_range, limit
         );
 
-        let response = reqwest::Client::new()
-            .get(&url)
-            .header(AUTHORIZATION, format!("Bearer {}", token))
-            .header(ACCEPT, "application/json")
-            .send()
-            .await?;
-
-        let tracks_data: serde_json::Value = response.json().await?;
+        let tracks_data: serde_json::Value = SpotifyApi::get_json(redis, account, &url).await?;
         Ok(tracks_data)
     }
 
     // Get user's top artists (for dashboard analytics)
-    pub async fn get_top_artists(redis: &mut RedisManager, time_range: &str, limit: i32) -> Result<serde_json::Value, AppError> {
-        if !Self::check_spotify_access(redis).await? {
-            return Err(AppError::SpotifyError("Not authenticated".to_string()));
-        }
-
-        let token: String = redis.connection.get("spotify:access_token").await?;
-        
+    pub async fn get_top_artists(redis: &mut RedisManager, account: &str, time_range: &str, limit: i32) -> Result<serde_json::Value, AppError> {
         let url = format!(
             "https://api.spotify.com/v1/me/top/artists?time_range={}&limit={}&offset=0",
             time_range, limit
         );
 
-        let response = reqwest::Client::new()
-            .get(&url)
-            .header(AUTHORIZATION, format!("Bearer {}", token))
-            .header(ACCEPT, "application/json")
-            .send()
-            .await?;
-
-        let artists_data: serde_json::Value = response.json().await?;
+        let artists_data: serde_json::Value = SpotifyApi::get_json(redis, account, &url).await?;
         Ok(artists_data)
     }
 
     // Get user's recently played tracks
-    pub async fn get_recently_played(redis: &mut RedisManager, limit: i32) -> Result<serde_json::Value, AppError> {
-        if !Self::check_spotify_access(redis).await? {
-            return Err(AppError::SpotifyError("Not authenticated".to_string()));
-        }
-
-        let token: String = redis.connection.get("spotify:access_token").await?;
-        
+    pub async fn get_recently_played(redis: &mut RedisManager, account: &str, limit: i32) -> Result<serde_json::Value, AppError> {
         let url = format!(
             "https://api.spotify.com/v1/me/player/recently-played?limit={}",
             limit
         );
 
-        let response = reqwest::Client::new()
-            .get(&url)
-            .header(AUTHORIZATION, format!("Bearer {}", token))
-            .header(ACCEPT, "application/json")
-            .send()
-            .await?;
-
-        let recent_data: serde_json::Value = response.json().await?;
+        let recent_data: serde_json::Value = SpotifyApi::get_json(redis, account, &url).await?;
         Ok(recent_data)
     }
 
-    // Get user's playlists
-    pub async fn get_user_playlists(redis: &mut RedisManager, limit: i32) -> Result<serde_json::Value, AppError> {
-        if !Self::check_spotify_access(redis).await? {
-            return Err(AppError::SpotifyError("Not authenticated".to_string()));
+    // Get recently played tracks as typed plays, optionally only those after a unix ms timestamp
+    pub async fn get_play_history(redis: &mut RedisManager, account: &str, after: Option<i64>) -> Result<RecentlyPlayed, AppError> {
+        let mut url = "https://api.spotify.com/v1/me/player/recently-played?limit=50".to_string();
+        if let Some(after) = after {
+            url.push_str(&format!("&after={}", after));
         }
 
-        let token: String = redis.connection.get("spotify:access_token").await?;
-        
+        let recent: RecentlyPlayed = SpotifyApi::get_json(redis, account, &url).await?;
+        Ok(recent)
+    }
+
+    // Get artist metadata (genres) for up to 50 artist ids
+    pub async fn get_artists(redis: &mut RedisManager, account: &str, ids: &[String]) -> Result<Vec<ArtistGenres>, AppError> {
+        let url = format!("https://api.spotify.com/v1/artists?ids={}", ids.join(","));
+
+        let artists_response: serde_json::Value = SpotifyApi::get_json(redis, account, &url).await?;
+        let artists: Vec<ArtistGenres> = serde_json::from_value(artists_response["artists"].clone())?;
+        Ok(artists)
+    }
+
+    // Get user's playlists
+    pub async fn get_user_playlists(redis: &mut RedisManager, account: &str, limit: i32) -> Result<serde_json::Value, AppError> {
         let url = format!(
             "https://api.spotify.com/v1/me/playlists?limit={}&offset=0",
             limit
         );
 
-        let response = reqwest::Client::new()
-            .get(&url)
-            .header(AUTHORIZATION, format!("Bearer {}", token))
-            .header(ACCEPT, "application/json")
-            .send()
-            .await?;
-
-        let playlists_data: serde_json::Value = response.json().await?;
+        let playlists_data: serde_json::Value = SpotifyApi::get_json(redis, account, &url).await?;
         Ok(playlists_data)
     }
+
+    // Playback control, requires the `user-modify-playback-state` scope
+    async fn player_command(
+        redis: &mut RedisManager,
+        account: &str,
+        method: Method,
+        path: &str,
+        query: &[(&str, String)],
+        body: Option<serde_json::Value>,
+    ) -> Result<(), AppError> {
+        let url = format!("https://api.spotify.com/v1/me/player{}", path);
+        let response = SpotifyApi::send(redis, account, method, &url, |request| {
+            let request = request.query(query);
+            match &body {
+                Some(body) => request.json(body),
+                None => request.header(CONTENT_LENGTH, "0"),
+            }
+        })
+        .await?;
+
+        SpotifyApi::check(response).await?;
+        Ok(())
+    }
+
+    fn device_query(device_id: &Option<String>) -> Vec<(&'static str, String)> {
+        device_id
+            .iter()
+            .map(|id| ("device_id", id.clone()))
+            .collect()
+    }
+
+    pub async fn play(
+        redis: &mut RedisManager,
+        account: &str,
+        device_id: &Option<String>,
+        request: Option<&PlayRequest>,
+    ) -> Result<(), AppError> {
+        let body = request.map(serde_json::to_value).transpose()?;
+        Self::player_command(redis, account, Method::PUT, "/play", &Self::device_query(device_id), body).await
+    }
+
+    pub async fn pause(redis: &mut RedisManager, account: &str, device_id: &Option<String>) -> Result<(), AppError> {
+        Self::player_command(redis, account, Method::PUT, "/pause", &Self::device_query(device_id), None).await
+    }
+
+    pub async fn next(redis: &mut RedisManager, account: &str, device_id: &Option<String>) -> Result<(), AppError> {
+        Self::player_command(redis, account, Method::POST, "/next", &Self::device_query(device_id), None).await
+    }
+
+    pub async fn previous(redis: &mut RedisManager, account: &str, device_id: &Option<String>) -> Result<(), AppError> {
+        Self::player_command(redis, account, Method::POST, "/previous", &Self::device_query(device_id), None).await
+    }
+
+    pub async fn seek(redis: &mut RedisManager, account: &str, position_ms: u32, device_id: &Option<String>) -> Result<(), AppError> {
+        let mut query = Self::device_query(device_id);
+        query.push(("position_ms", position_ms.to_string()));
+        Self::player_command(redis, account, Method::PUT, "/seek", &query, None).await
+    }
+
+    pub async fn set_volume(redis: &mut RedisManager, account: &str, volume_percent: u8, device_id: &Option<String>) -> Result<(), AppError> {
+        let mut query = Self::device_query(device_id);
+        query.push(("volume_percent", volume_percent.to_string()));
+        Self::player_command(redis, account, Method::PUT, "/volume", &query, None).await
+    }
+
+    pub async fn set_shuffle(redis: &mut RedisManager, account: &str, state: bool, device_id: &Option<String>) -> Result<(), AppError> {
+        let mut query = Self::device_query(device_id);
+        query.push(("state", state.to_string()));
+        Self::player_command(redis, account, Method::PUT, "/shuffle", &query, None).await
+    }
+
+    pub async fn set_repeat(redis: &mut RedisManager, account: &str, state: &str, device_id: &Option<String>) -> Result<(), AppError> {
+        let mut query = Self::device_query(device_id);
+        query.push(("state", state.to_string()));
+        Self::player_command(redis, account, Method::PUT, "/repeat", &query, None).await
+    }
+
+    pub async fn transfer_playback(redis: &mut RedisManager, account: &str, request: &TransferRequest) -> Result<(), AppError> {
+        let body = serde_json::json!({ "device_ids": [request.device_id], "play": request.play });
+        Self::player_command(redis, account, Method::PUT, "", &[], Some(body)).await
+    }
+
+    pub async fn add_to_queue(redis: &mut RedisManager, account: &str, uri: &str, device_id: &Option<String>) -> Result<(), AppError> {
+        let mut query = Self::device_query(device_id);
+        query.push(("uri", uri.to_string()));
+        Self::player_command(redis, account, Method::POST, "/queue", &query, None).await
+    }
 }
@@ -1,5 +1,12 @@
+pub mod api;
 pub mod entity;
 pub mod handler;
 pub mod manager;
+pub mod oauth;
+pub mod provider;
+pub mod repository;
+pub mod stream;
 
 pub use manager::SpotifyManager;
+pub use provider::SpotifyProvider;
+pub use repository::SpotifyRepository;
@@ -0,0 +1,211 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use envconfig::Envconfig;
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use redis::{aio::ConnectionManager, AsyncCommands};
+use sha2::{Digest, Sha256};
+
+use crate::{config::Config, db::redis::RedisManager, error::{AppError, SpotifyError}};
+
+use super::entity::{AuthData, SpotifyToken, TokenError};
+
+// How long a user has to complete the Spotify consent screen
+const STATE_TTL_SECS: i64 = 600;
+
+const SCOPES: &str = "user-read-private+user-read-playback-state+user-read-currently-playing+user-modify-playback-state+user-read-recently-played+user-top-read+playlist-read-private";
+
+// Authorization code flow with a signed, single-use `state`, and PKCE when no client secret is set
+#[derive(Clone)]
+pub struct SpotifyOAuth;
+
+impl SpotifyOAuth {
+    // Build the Spotify authorize URL, remembering the state (and PKCE verifier) in Redis
+    pub async fn authorize_url(redis: &mut RedisManager) -> Result<String, AppError> {
+        let config = Config::init_from_env()?;
+        let nonce = random_token(24);
+        let expires_at = chrono::Utc::now().timestamp() + STATE_TTL_SECS;
+        let payload = format!("{}.{}", nonce, expires_at);
+        let signature = Self::sign(redis, &payload).await?;
+        let state = format!("{}.{}", payload, signature);
+
+        let verifier = if config.spotify_client_secret.is_empty() {
+            random_token(64)
+        } else {
+            String::new()
+        };
+        redis::cmd("SET")
+            .arg(format!("spotify:oauth_state:{}", nonce))
+            .arg(&verifier)
+            .arg("EX")
+            .arg(STATE_TTL_SECS)
+            .query_async::<ConnectionManager, String>(&mut redis.connection)
+            .await?;
+
+        let mut url = format!(
+            "https://accounts.spotify.com/authorize?client_id={}&response_type=code&scope={}&redirect_uri={}&state={}",
+            config.spotify_client_id, SCOPES, config.spotify_redirect_uri, state
+        );
+        if !verifier.is_empty() {
+            let challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()));
+            url.push_str(&format!(
+                "&code_challenge_method=S256&code_challenge={}",
+                challenge
+            ));
+        }
+        Ok(url)
+    }
+
+    // Check signature and expiry, then consume the state; returns the PKCE verifier if any
+    pub async fn verify_state(redis: &mut RedisManager, state: &str) -> Result<Option<String>, AppError> {
+        let secret = Self::state_secret(redis).await?;
+        let nonce = check_state(&secret, state, chrono::Utc::now().timestamp())?;
+
+        let verifier: Option<String> = redis::cmd("GETDEL")
+            .arg(format!("spotify:oauth_state:{}", nonce))
+            .query_async(&mut redis.connection)
+            .await?;
+        consume_verifier(verifier)
+    }
+
+    // Exchange an authorization code, using PKCE when a verifier was issued
+    pub async fn exchange_code(code: &str, verifier: Option<String>) -> Result<SpotifyToken, AppError> {
+        let config = Config::init_from_env()?;
+        let pkce = verifier.is_some();
+        let auth_data = AuthData {
+            code: code.to_string(),
+            grant_type: "authorization_code".into(),
+            redirect_uri: config.spotify_redirect_uri,
+            client_id: pkce.then(|| config.spotify_client_id.clone()),
+            code_verifier: verifier,
+        };
+
+        let mut request = reqwest::Client::new()
+            .post("https://accounts.spotify.com/api/token")
+            .form(&auth_data);
+        if !pkce {
+            request = request.basic_auth(config.spotify_client_id, Some(config.spotify_client_secret));
+        }
+
+        let response = request.send().await?;
+        if !response.status().is_success() {
+            let error: TokenError = response.json().await?;
+            return Err(SpotifyError::Other(format!(
+                "{}: {}",
+                error.error, error.error_description
+            )).into());
+        }
+        let token: SpotifyToken = response.json().await?;
+        Ok(token)
+    }
+
+    async fn sign(redis: &mut RedisManager, payload: &str) -> Result<String, AppError> {
+        let secret = Self::state_secret(redis).await?;
+        sign_payload(&secret, payload)
+    }
+
+    // `SPOTIFY_STATE_SECRET`, or a random secret shared through Redis by every instance
+    async fn state_secret(redis: &mut RedisManager) -> Result<String, AppError> {
+        let config = Config::init_from_env()?;
+        if !config.spotify_state_secret.is_empty() {
+            return Ok(config.spotify_state_secret);
+        }
+        let _: bool = redis
+            .connection
+            .set_nx("spotify:oauth_secret", random_token(32))
+            .await?;
+        let secret: String = redis.connection.get("spotify:oauth_secret").await?;
+        Ok(secret)
+    }
+}
+
+fn invalid_state() -> AppError {
+    AppError::BadRequest("Invalid or expired state".to_string())
+}
+
+fn sign_payload(secret: &str, payload: &str) -> Result<String, AppError> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .map_err(|e| SpotifyError::Other(e.to_string()))?;
+    mac.update(payload.as_bytes());
+    Ok(URL_SAFE_NO_PAD.encode(mac.finalize().into_bytes()))
+}
+
+// Signature and expiry half of `verify_state`; returns the nonce to consume
+fn check_state<'a>(secret: &str, state: &'a str, now: i64) -> Result<&'a str, AppError> {
+    let (payload, signature) = state.rsplit_once('.').ok_or_else(invalid_state)?;
+    let (nonce, expires_at) = payload.split_once('.').ok_or_else(invalid_state)?;
+    let expected = sign_payload(secret, payload)?;
+    if !crate::auth::constant_time_eq(signature.as_bytes(), expected.as_bytes()) {
+        return Err(invalid_state());
+    }
+    let expires_at: i64 = expires_at.parse().map_err(|_| invalid_state())?;
+    if expires_at < now {
+        return Err(invalid_state());
+    }
+    Ok(nonce)
+}
+
+// A state already taken by GETDEL (or never issued) is a replay
+fn consume_verifier(stored: Option<String>) -> Result<Option<String>, AppError> {
+    match stored {
+        Some(verifier) if verifier.is_empty() => Ok(None),
+        Some(verifier) => Ok(Some(verifier)),
+        None => Err(invalid_state()),
+    }
+}
+
+fn random_token(bytes: usize) -> String {
+    let mut buffer = vec![0u8; bytes];
+    rand::thread_rng().fill_bytes(&mut buffer);
+    URL_SAFE_NO_PAD.encode(buffer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &str = "state-secret";
+    const NOW: i64 = 1_800_000_000;
+
+    fn issue(secret: &str, nonce: &str, expires_at: i64) -> String {
+        let payload = format!("{}.{}", nonce, expires_at);
+        format!("{}.{}", payload, sign_payload(secret, &payload).unwrap())
+    }
+
+    fn rejected<T>(result: Result<T, AppError>) -> bool {
+        matches!(result, Err(AppError::BadRequest(_)))
+    }
+
+    #[test]
+    fn check_state_returns_nonce() {
+        let state = issue(SECRET, "abc", NOW + STATE_TTL_SECS);
+        assert_eq!(check_state(SECRET, &state, NOW).unwrap(), "abc");
+    }
+
+    #[test]
+    fn check_state_rejects_expired() {
+        let state = issue(SECRET, "abc", NOW - 1);
+        assert!(rejected(check_state(SECRET, &state, NOW)));
+    }
+
+    #[test]
+    fn check_state_rejects_bad_signature() {
+        let state = issue("other-secret", "abc", NOW + STATE_TTL_SECS);
+        assert!(rejected(check_state(SECRET, &state, NOW)));
+
+        // Extending the expiry invalidates the signature
+        let state = issue(SECRET, "abc", NOW + STATE_TTL_SECS);
+        let (_, signature) = state.rsplit_once('.').unwrap();
+        let forged = format!("abc.{}.{}", NOW + 10 * STATE_TTL_SECS, signature);
+        assert!(rejected(check_state(SECRET, &forged, NOW)));
+
+        assert!(rejected(check_state(SECRET, "garbage", NOW)));
+    }
+
+    #[test]
+    fn consume_verifier_rejects_replay() {
+        assert_eq!(consume_verifier(Some("verifier".to_string())).unwrap().as_deref(), Some("verifier"));
+        assert_eq!(consume_verifier(Some(String::new())).unwrap(), None);
+        // Second use of a state: GETDEL already removed it
+        assert!(rejected(consume_verifier(None)));
+    }
+}
@@ -0,0 +1,152 @@
+use std::time::Duration;
+
+use actix_web::web;
+use async_trait::async_trait;
+use envconfig::Envconfig;
+use log::{error, info};
+use serde_json::{json, Value};
+use tokio::sync::Mutex;
+
+use crate::{client::NullClient, config::Config, error::AppError, provider::{Provider, ProviderHealth}};
+
+use super::{
+    handler::{
+        accounts, authorize, callback, current, default_account, devices, history, player_next,
+        player_pause, player_play, player_previous, player_queue, player_repeat, player_seek,
+        player_shuffle, player_transfer, player_volume, playlists, queue, realtime_info,
+        recently_played, revoke_account, stats, stream, stream_ws, top_artists, top_tracks,
+    },
+    entity::Playback,
+    stream::PlaybackHub,
+    SpotifyManager, SpotifyRepository,
+};
+
+pub struct SpotifyProvider {
+    playback: web::Data<PlaybackHub>,
+}
+
+impl Default for SpotifyProvider {
+    fn default() -> Self {
+        Self {
+            playback: web::Data::new(PlaybackHub::default()),
+        }
+    }
+}
+
+#[async_trait]
+impl Provider for SpotifyProvider {
+    fn name(&self) -> &'static str {
+        "spotify"
+    }
+
+    fn missing_config(&self, config: &Config) -> Vec<&'static str> {
+        // Without SPOTIFY_CLIENT_SECRET the authorization flow falls back to PKCE
+        if config.spotify_client_id.is_empty() {
+            vec!["SPOTIFY_CLIENT_ID"]
+        } else {
+            Vec::new()
+        }
+    }
+
+    fn routes(&self, cfg: &mut web::ServiceConfig) {
+        cfg.app_data(web::Data::clone(&self.playback));
+        cfg.service(current);
+        cfg.service(authorize);
+        cfg.service(callback);
+        cfg.service(accounts);
+        cfg.service(revoke_account);
+        cfg.service(default_account);
+        cfg.service(realtime_info);
+        cfg.service(stream);
+        cfg.service(stream_ws);
+        cfg.service(devices);
+        cfg.service(queue);
+        cfg.service(top_tracks);
+        cfg.service(top_artists);
+        cfg.service(recently_played);
+        cfg.service(playlists);
+        cfg.service(history);
+        cfg.service(stats);
+        // Playback control
+        cfg.service(player_play);
+        cfg.service(player_pause);
+        cfg.service(player_next);
+        cfg.service(player_previous);
+        cfg.service(player_seek);
+        cfg.service(player_volume);
+        cfg.service(player_shuffle);
+        cfg.service(player_repeat);
+        cfg.service(player_transfer);
+        cfg.service(player_queue);
+    }
+
+    async fn health(&self, state: &web::Data<Mutex<NullClient>>) -> Result<ProviderHealth, AppError> {
+        let mut redis = state.lock().await.redis.clone();
+        if SpotifyManager::check_spotify_auth(&mut redis).await? {
+            Ok(ProviderHealth::Up)
+        } else {
+            Ok(ProviderHealth::NotConfigured("No Spotify account linked".to_string()))
+        }
+    }
+
+    fn refresh_interval(&self) -> Option<Duration> {
+        let config = Config::init_from_env().ok()?;
+        Some(Duration::from_secs(config.spotify_history_interval.max(60)))
+    }
+
+    // Ingest recently played tracks into the listening history
+    async fn refresh(&self, state: &web::Data<Mutex<NullClient>>) -> Result<(), AppError> {
+        let data = state.lock().await;
+        let mut redis = data.redis.clone();
+        let pool = data.postgres.pool.clone();
+        drop(data);
+
+        if !SpotifyManager::check_spotify_auth(&mut redis).await? {
+            return Ok(());
+        }
+
+        // Resolving the default account migrates legacy single-account credentials
+        let default = SpotifyManager::resolve_account(&mut redis, None).await?;
+        SpotifyRepository::claim_unassigned_plays(&pool, &default).await?;
+
+        for account in SpotifyManager::accounts(&mut redis).await? {
+            let after = SpotifyRepository::latest_played_at(&pool, &account)
+                .await?
+                .map(|played_at| played_at.timestamp_millis());
+            let recent = match SpotifyManager::get_play_history(&mut redis, &account, after).await {
+                Ok(recent) => recent,
+                Err(e) => {
+                    error!("Failed to fetch Spotify history for {}: {}", account, e);
+                    continue;
+                }
+            };
+            let inserted = SpotifyRepository::record_plays(&pool, &account, &recent.items).await?;
+            if inserted > 0 {
+                info!("Stored {} new Spotify plays for {}", inserted, account);
+            }
+        }
+
+        // Fill in genres for newly seen artists
+        let missing = SpotifyRepository::missing_artist_ids(&pool, 50).await?;
+        if !missing.is_empty() {
+            let artists = SpotifyManager::get_artists(&mut redis, &default, &missing).await?;
+            SpotifyRepository::record_artists(&pool, &artists).await?;
+        }
+        Ok(())
+    }
+
+    // Current track of the default account; nothing playing is reported as idle, not an error
+    async fn now(&self, state: &web::Data<Mutex<NullClient>>) -> Result<Option<Value>, AppError> {
+        let mut redis = state.lock().await.redis.clone();
+        let account = SpotifyManager::resolve_account(&mut redis, None).await?;
+        let section = match SpotifyManager::get_playback(&mut redis, &account).await? {
+            Playback::Playing(song) => {
+                let state = if song.playback.is_playing { "playing" } else { "paused" };
+                json!({ "state": state, "song": song })
+            }
+            Playback::NoTrack => json!({ "state": "idle", "song": null }),
+            Playback::NoDevice => json!({ "state": "no_device", "song": null }),
+        };
+        Ok(Some(section))
+    }
+}
@@ -0,0 +1,351 @@
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Postgres};
+
+use crate::error::AppError;
+
+use super::entity::{
+    ArtistGenres, HeatmapCell, ListeningBucket, ListeningStats, PlayHistory, StoredPlay, Streak,
+    TopItem,
+};
+
+#[derive(Clone)]
+pub struct SpotifyRepository;
+
+impl SpotifyRepository {
+    // Store plays, skipping local tracks and any `played_at` already recorded
+    pub async fn record_plays(pool: &Pool<Postgres>, account: &str, plays: &[PlayHistory]) -> Result<u64, AppError> {
+        let mut tx = pool.begin().await?;
+        let mut inserted = 0;
+
+        for play in plays {
+            let (Some(track_id), Some(album_id)) = (&play.track.id, &play.track.album.id) else {
+                continue;
+            };
+            let artists = serde_json::to_value(&play.track.artists)?;
+            let result = sqlx::query(
+                "INSERT INTO spotify_plays (
+                    account, played_at, track_id, track_name, artists, album_id, album_name,
+                    context_uri, context_type, duration_ms
+                 ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+                 ON CONFLICT (account, played_at) DO NOTHING",
+            )
+            .bind(account)
+            .bind(play.played_at)
+            .bind(track_id)
+            .bind(&play.track.name)
+            .bind(artists)
+            .bind(album_id)
+            .bind(&play.track.album.name)
+            .bind(play.context.as_ref().map(|c| &c.uri))
+            .bind(play.context.as_ref().map(|c| &c.context_type))
+            .bind(play.track.duration_ms)
+            .execute(&mut *tx)
+            .await?;
+            inserted += result.rows_affected();
+        }
+
+        tx.commit().await?;
+        Ok(inserted)
+    }
+
+    // Most recent `played_at` stored, used as the ingestion watermark
+    pub async fn latest_played_at(pool: &Pool<Postgres>, account: &str) -> Result<Option<DateTime<Utc>>, AppError> {
+        let latest: Option<DateTime<Utc>> =
+            sqlx::query_scalar("SELECT MAX(played_at) FROM spotify_plays WHERE account = $1")
+                .bind(account)
+                .fetch_one(pool)
+                .await?;
+        Ok(latest)
+    }
+
+    // Hand plays ingested before accounts existed to the account they came from
+    pub async fn claim_unassigned_plays(pool: &Pool<Postgres>, account: &str) -> Result<u64, AppError> {
+        let mut tx = pool.begin().await?;
+
+        // Legacy rows already re-ingested under the account would collide on (account, played_at)
+        sqlx::query(
+            "DELETE FROM spotify_plays legacy
+             WHERE legacy.account = ''
+               AND EXISTS (
+                   SELECT 1 FROM spotify_plays claimed
+                   WHERE claimed.account = $1 AND claimed.played_at = legacy.played_at
+               )",
+        )
+        .bind(account)
+        .execute(&mut *tx)
+        .await?;
+
+        let result = sqlx::query("UPDATE spotify_plays SET account = $1 WHERE account = ''")
+            .bind(account)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(result.rows_affected())
+    }
+
+    // Page through stored plays, newest first, strictly before `before`
+    pub async fn history(
+        pool: &Pool<Postgres>,
+        account: &str,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+        before: Option<DateTime<Utc>>,
+        limit: i64,
+    ) -> Result<Vec<StoredPlay>, AppError> {
+        let plays = sqlx::query_as::<_, StoredPlay>(
+            "SELECT played_at, track_id, track_name, artists, album_id, album_name,
+                    context_uri, context_type, duration_ms
+             FROM spotify_plays
+             WHERE account = $5
+               AND ($1::timestamptz IS NULL OR played_at >= $1)
+               AND ($2::timestamptz IS NULL OR played_at < $2)
+               AND ($3::timestamptz IS NULL OR played_at < $3)
+             ORDER BY played_at DESC
+             LIMIT $4",
+        )
+        .bind(from)
+        .bind(to)
+        .bind(before)
+        .bind(limit)
+        .bind(account)
+        .fetch_all(pool)
+        .await?;
+        Ok(plays)
+    }
+
+    // Artists seen in the history that have no metadata yet
+    pub async fn missing_artist_ids(pool: &Pool<Postgres>, limit: i64) -> Result<Vec<String>, AppError> {
+        let ids: Vec<String> = sqlx::query_scalar(
+            "SELECT DISTINCT a->>'id'
+             FROM spotify_plays p
+             CROSS JOIN jsonb_array_elements(p.artists) a
+             LEFT JOIN spotify_artists sa ON sa.id = a->>'id'
+             WHERE a->>'id' IS NOT NULL AND sa.id IS NULL
+             LIMIT $1",
+        )
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+        Ok(ids)
+    }
+
+    pub async fn record_artists(pool: &Pool<Postgres>, artists: &[ArtistGenres]) -> Result<(), AppError> {
+        let mut tx = pool.begin().await?;
+
+        for artist in artists {
+            sqlx::query(
+                "INSERT INTO spotify_artists (id, name, genres) VALUES ($1, $2, $3)
+                 ON CONFLICT (id) DO UPDATE
+                 SET name = EXCLUDED.name, genres = EXCLUDED.genres, fetched_at = NOW()",
+            )
+            .bind(&artist.id)
+            .bind(&artist.name)
+            .bind(&artist.genres)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    // Listening analytics for a date range, bucketed in the given timezone
+    pub async fn stats(
+        pool: &Pool<Postgres>,
+        account: &str,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+        timezone: &str,
+        limit: i64,
+    ) -> Result<ListeningStats, AppError> {
+        let totals = sqlx::query_as::<_, (i64, f64)>(
+            "SELECT COUNT(*), COALESCE(SUM(duration_ms), 0)::float8 / 60000
+             FROM spotify_plays
+             WHERE account = $3
+               AND ($1::timestamptz IS NULL OR played_at >= $1)
+               AND ($2::timestamptz IS NULL OR played_at < $2)",
+        )
+        .bind(from)
+        .bind(to)
+        .bind(account)
+        .fetch_one(pool);
+
+        let top_tracks = sqlx::query_as::<_, TopItem>(
+            "SELECT track_id AS id, MAX(track_name) AS name, COUNT(*) AS plays,
+                    SUM(duration_ms)::float8 / 60000 AS minutes
+             FROM spotify_plays
+             WHERE account = $4
+               AND ($1::timestamptz IS NULL OR played_at >= $1)
+               AND ($2::timestamptz IS NULL OR played_at < $2)
+             GROUP BY track_id
+             ORDER BY plays DESC, minutes DESC
+             LIMIT $3",
+        )
+        .bind(from)
+        .bind(to)
+        .bind(limit)
+        .bind(account)
+        .fetch_all(pool);
+
+        let top_artists = sqlx::query_as::<_, TopItem>(
+            "SELECT a->>'id' AS id, MAX(a->>'name') AS name, COUNT(*) AS plays,
+                    SUM(p.duration_ms)::float8 / 60000 AS minutes
+             FROM spotify_plays p
+             CROSS JOIN jsonb_array_elements(p.artists) a
+             WHERE p.account = $4
+               AND ($1::timestamptz IS NULL OR p.played_at >= $1)
+               AND ($2::timestamptz IS NULL OR p.played_at < $2)
+             GROUP BY a->>'id'
+             ORDER BY plays DESC, minutes DESC
+             LIMIT $3",
+        )
+        .bind(from)
+        .bind(to)
+        .bind(limit)
+        .bind(account)
+        .fetch_all(pool);
+
+        let top_albums = sqlx::query_as::<_, TopItem>(
+            "SELECT album_id AS id, MAX(album_name) AS name, COUNT(*) AS plays,
+                    SUM(duration_ms)::float8 / 60000 AS minutes
+             FROM spotify_plays
+             WHERE account = $4
+               AND ($1::timestamptz IS NULL OR played_at >= $1)
+               AND ($2::timestamptz IS NULL OR played_at < $2)
+             GROUP BY album_id
+             ORDER BY plays DESC, minutes DESC
+             LIMIT $3",
+        )
+        .bind(from)
+        .bind(to)
+        .bind(limit)
+        .bind(account)
+        .fetch_all(pool);
+
+        let top_genres = sqlx::query_as::<_, TopItem>(
+            "SELECT NULL::text AS id, g.genre AS name, COUNT(*) AS plays,
+                    SUM(p.duration_ms)::float8 / 60000 AS minutes
+             FROM spotify_plays p
+             CROSS JOIN jsonb_array_elements(p.artists) a
+             JOIN spotify_artists sa ON sa.id = a->>'id'
+             CROSS JOIN unnest(sa.genres) AS g(genre)
+             WHERE p.account = $4
+               AND ($1::timestamptz IS NULL OR p.played_at >= $1)
+               AND ($2::timestamptz IS NULL OR p.played_at < $2)
+             GROUP BY g.genre
+             ORDER BY plays DESC, minutes DESC
+             LIMIT $3",
+        )
+        .bind(from)
+        .bind(to)
+        .bind(limit)
+        .bind(account)
+        .fetch_all(pool);
+
+        let by_hour = sqlx::query_as::<_, ListeningBucket>(
+            "SELECT EXTRACT(HOUR FROM played_at AT TIME ZONE $3)::int AS bucket, COUNT(*) AS plays,
+                    SUM(duration_ms)::float8 / 60000 AS minutes
+             FROM spotify_plays
+             WHERE account = $4
+               AND ($1::timestamptz IS NULL OR played_at >= $1)
+               AND ($2::timestamptz IS NULL OR played_at < $2)
+             GROUP BY bucket
+             ORDER BY bucket",
+        )
+        .bind(from)
+        .bind(to)
+        .bind(timezone)
+        .bind(account)
+        .fetch_all(pool);
+
+        let by_weekday = sqlx::query_as::<_, ListeningBucket>(
+            "SELECT EXTRACT(ISODOW FROM played_at AT TIME ZONE $3)::int AS bucket, COUNT(*) AS plays,
+                    SUM(duration_ms)::float8 / 60000 AS minutes
+             FROM spotify_plays
+             WHERE account = $4
+               AND ($1::timestamptz IS NULL OR played_at >= $1)
+               AND ($2::timestamptz IS NULL OR played_at < $2)
+             GROUP BY bucket
+             ORDER BY bucket",
+        )
+        .bind(from)
+        .bind(to)
+        .bind(timezone)
+        .bind(account)
+        .fetch_all(pool);
+
+        let heatmap = sqlx::query_as::<_, HeatmapCell>(
+            "SELECT EXTRACT(ISODOW FROM played_at AT TIME ZONE $3)::int AS weekday,
+                    EXTRACT(HOUR FROM played_at AT TIME ZONE $3)::int AS hour,
+                    COUNT(*) AS plays, SUM(duration_ms)::float8 / 60000 AS minutes
+             FROM spotify_plays
+             WHERE account = $4
+               AND ($1::timestamptz IS NULL OR played_at >= $1)
+               AND ($2::timestamptz IS NULL OR played_at < $2)
+             GROUP BY weekday, hour
+             ORDER BY weekday, hour",
+        )
+        .bind(from)
+        .bind(to)
+        .bind(timezone)
+        .bind(account)
+        .fetch_all(pool);
+
+        // Gaps-and-islands over distinct listening days
+        let streaks = sqlx::query_as::<_, Streak>(
+            "WITH days AS (
+                SELECT DISTINCT (played_at AT TIME ZONE $3)::date AS day
+                FROM spotify_plays
+                WHERE account = $4
+                  AND ($1::timestamptz IS NULL OR played_at >= $1)
+                  AND ($2::timestamptz IS NULL OR played_at < $2)
+             ), islands AS (
+                SELECT day, day - (ROW_NUMBER() OVER (ORDER BY day))::int AS island
+                FROM days
+             )
+             SELECT MIN(day) AS start, MAX(day) AS \"end\", COUNT(*) AS days,
+                    MAX(day) >= (NOW() AT TIME ZONE $3)::date - 1 AS \"current\"
+             FROM islands
+             GROUP BY island
+             ORDER BY days DESC, start DESC",
+        )
+        .bind(from)
+        .bind(to)
+        .bind(timezone)
+        .bind(account)
+        .fetch_all(pool);
+
+        let (
+            (total_plays, minutes_listened),
+            top_tracks,
+            top_artists,
+            top_albums,
+            top_genres,
+            by_hour,
+            by_weekday,
+            heatmap,
+            streaks,
+        ) = tokio::try_join!(
+            totals, top_tracks, top_artists, top_albums, top_genres, by_hour, by_weekday, heatmap,
+            streaks
+        )?;
+
+        Ok(ListeningStats {
+            from,
+            to,
+            timezone: timezone.to_string(),
+            total_plays,
+            minutes_listened,
+            top_tracks,
+            top_artists,
+            top_albums,
+            top_genres,
+            by_hour,
+            by_weekday,
+            heatmap,
+            longest_streak: streaks.first().cloned(),
+            current_streak: streaks.iter().find(|streak| streak.current).cloned(),
+        })
+    }
+}
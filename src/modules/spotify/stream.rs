@@ -0,0 +1,373 @@
+use std::{
+    collections::HashMap,
+    convert::Infallible,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, RwLock,
+    },
+    time::{Duration, Instant},
+};
+
+use actix_web::web::{self, Bytes};
+use actix_ws::{Message, MessageStream, Session};
+use envconfig::Envconfig;
+use futures::{stream, Stream, StreamExt};
+use log::{debug, info};
+use tokio::sync::{
+    broadcast::{self, error::RecvError},
+    Mutex,
+};
+
+use crate::{client::NullClient, config::Config};
+
+use super::{
+    entity::{Playback, RealTimeSongInfo, RealTimeUpdate},
+    SpotifyManager,
+};
+
+// Drift between expected and reported progress that counts as a seek
+const SEEK_THRESHOLD_MS: i64 = 3000;
+// Idle time after which an SSE comment is sent to keep proxies from closing the stream
+const KEEP_ALIVE: Duration = Duration::from_secs(15);
+
+// One playback stream per linked account
+#[derive(Default)]
+pub struct PlaybackHub {
+    streams: std::sync::Mutex<HashMap<String, Arc<PlaybackStream>>>,
+}
+
+impl PlaybackHub {
+    pub fn get(&self, account: &str) -> Arc<PlaybackStream> {
+        let mut streams = self.streams.lock().unwrap_or_else(|e| e.into_inner());
+        let stream = streams
+            .entry(account.to_string())
+            .or_insert_with(|| Arc::new(PlaybackStream::new(account)));
+        Arc::clone(stream)
+    }
+}
+
+// One shared poller of an account's playback state, fanned out to every stream subscriber
+pub struct PlaybackStream {
+    account: String,
+    sender: broadcast::Sender<RealTimeUpdate>,
+    latest: RwLock<Option<RealTimeUpdate>>,
+    running: AtomicBool,
+}
+
+impl PlaybackStream {
+    fn new(account: &str) -> Self {
+        let (sender, _) = broadcast::channel(64);
+        Self {
+            account: account.to_string(),
+            sender,
+            latest: RwLock::new(None),
+            running: AtomicBool::new(false),
+        }
+    }
+
+    // Subscribe to updates, starting the poller if nobody was listening
+    pub fn subscribe(
+        self: &Arc<Self>,
+        state: &web::Data<Mutex<NullClient>>,
+    ) -> broadcast::Receiver<RealTimeUpdate> {
+        let receiver = self.sender.subscribe();
+        if !self.running.swap(true, Ordering::SeqCst) {
+            let stream = Arc::clone(self);
+            let state = web::Data::clone(state);
+            tokio::spawn(async move { stream.poll(state).await });
+        }
+        receiver
+    }
+
+    // Last known playback state, sent to new subscribers as a `snapshot`
+    pub fn latest(&self) -> Option<RealTimeUpdate> {
+        self.latest.read().ok().and_then(|latest| latest.clone())
+    }
+
+    // Server-Sent Events body: the latest snapshot, then every update
+    pub fn sse(
+        self: &Arc<Self>,
+        state: &web::Data<Mutex<NullClient>>,
+    ) -> impl Stream<Item = Result<Bytes, Infallible>> {
+        let receiver = self.subscribe(state);
+        let initial = self.latest();
+        stream::unfold((receiver, initial), |(mut receiver, initial)| async move {
+            if let Some(update) = initial {
+                return Some((Ok(sse_event(&update)), (receiver, None)));
+            }
+            loop {
+                match tokio::time::timeout(KEEP_ALIVE, receiver.recv()).await {
+                    Ok(Ok(update)) => return Some((Ok(sse_event(&update)), (receiver, None))),
+                    Ok(Err(RecvError::Lagged(_))) => continue,
+                    Ok(Err(RecvError::Closed)) => return None,
+                    Err(_) => {
+                        return Some((Ok(Bytes::from_static(b": keep-alive\n\n")), (receiver, None)))
+                    }
+                }
+            }
+        })
+    }
+
+    // Forward updates to a WebSocket session until either side closes
+    pub async fn websocket(
+        self: Arc<Self>,
+        state: web::Data<Mutex<NullClient>>,
+        mut session: Session,
+        mut messages: MessageStream,
+    ) {
+        let mut receiver = self.subscribe(&state);
+        if let Some(update) = self.latest() {
+            if send_ws(&mut session, &update).await.is_err() {
+                return;
+            }
+        }
+
+        loop {
+            tokio::select! {
+                update = receiver.recv() => match update {
+                    Ok(update) => {
+                        if send_ws(&mut session, &update).await.is_err() {
+                            return;
+                        }
+                    }
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => break,
+                },
+                message = messages.next() => match message {
+                    Some(Ok(Message::Ping(bytes))) => {
+                        let _ = session.pong(&bytes).await;
+                    }
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                    _ => {}
+                },
+            }
+        }
+
+        let _ = session.close(None).await;
+    }
+
+    async fn poll(&self, state: web::Data<Mutex<NullClient>>) {
+        let interval = Config::init_from_env()
+            .map(|config| config.spotify_stream_interval)
+            .unwrap_or(3)
+            .max(1);
+        let mut ticker = tokio::time::interval(Duration::from_secs(interval));
+        let mut previous: Option<(RealTimeSongInfo, Instant)> = None;
+        info!("Spotify playback poller started for {}", self.account);
+
+        loop {
+            ticker.tick().await;
+            if self.sender.receiver_count() == 0 {
+                self.running.store(false, Ordering::SeqCst);
+                // Keep going if a subscriber arrived after the check and nobody restarted us
+                if self.sender.receiver_count() == 0 || self.running.swap(true, Ordering::SeqCst) {
+                    break;
+                }
+            }
+
+            let mut redis = state.lock().await.redis.clone();
+            let playback = match SpotifyManager::get_playback(&mut redis, &self.account).await {
+                Ok(playback) => playback,
+                Err(e) => {
+                    debug!("Spotify playback poll skipped: {}", e);
+                    continue;
+                }
+            };
+            let timestamp = chrono::Utc::now().timestamp();
+
+            let current = match playback {
+                Playback::Playing(current) => *current,
+                // Playback ended or the device went away: tell subscribers once, then forget the track
+                Playback::NoDevice | Playback::NoTrack => {
+                    if let Some((last, _)) = previous.take() {
+                        let mut events = vec!["stopped"];
+                        if matches!(playback, Playback::NoDevice) && last.device.is_some() {
+                            events.push("device_changed");
+                        }
+                        for event_type in events {
+                            let _ = self.sender.send(RealTimeUpdate {
+                                event_type: event_type.to_string(),
+                                timestamp,
+                                data: serde_json::Value::Null,
+                            });
+                        }
+                    }
+                    if let Ok(mut latest) = self.latest.write() {
+                        *latest = Some(RealTimeUpdate {
+                            event_type: "snapshot".to_string(),
+                            timestamp,
+                            data: serde_json::Value::Null,
+                        });
+                    }
+                    continue;
+                }
+            };
+
+            let events = detect_events(previous.as_ref(), &current);
+            let data = match serde_json::to_value(&current) {
+                Ok(data) => data,
+                Err(_) => continue,
+            };
+            for event_type in events {
+                let _ = self.sender.send(RealTimeUpdate {
+                    event_type: event_type.to_string(),
+                    timestamp,
+                    data: data.clone(),
+                });
+            }
+            if let Ok(mut latest) = self.latest.write() {
+                *latest = Some(RealTimeUpdate {
+                    event_type: "snapshot".to_string(),
+                    timestamp,
+                    data,
+                });
+            }
+            previous = Some((current, Instant::now()));
+        }
+
+        info!("Spotify playback poller for {} stopped, no subscribers left", self.account);
+    }
+}
+
+fn sse_event(update: &RealTimeUpdate) -> Bytes {
+    let data = serde_json::to_string(update).unwrap_or_default();
+    Bytes::from(format!("event: {}\ndata: {}\n\n", update.event_type, data))
+}
+
+async fn send_ws(session: &mut Session, update: &RealTimeUpdate) -> Result<(), actix_ws::Closed> {
+    let text = serde_json::to_string(update).unwrap_or_default();
+    session.text(text).await
+}
+
+// Compare two polls and name what changed between them
+fn detect_events(
+    previous: Option<&(RealTimeSongInfo, Instant)>,
+    current: &RealTimeSongInfo,
+) -> Vec<&'static str> {
+    let Some((previous, polled_at)) = previous else {
+        return vec!["track_changed"];
+    };
+
+    let mut events = Vec::new();
+    if previous.track.id != current.track.id {
+        events.push("track_changed");
+    } else {
+        match (previous.playback.is_playing, current.playback.is_playing) {
+            (true, false) => events.push("paused"),
+            (false, true) => events.push("resumed"),
+            _ => {}
+        }
+
+        let elapsed = if previous.playback.is_playing {
+            polled_at.elapsed().as_millis() as i64
+        } else {
+            0
+        };
+        let expected = previous.playback.progress_ms + elapsed;
+        if current.playback.is_playing == previous.playback.is_playing
+            && (current.playback.progress_ms - expected).abs() > SEEK_THRESHOLD_MS
+        {
+            events.push("seeked");
+        }
+    }
+
+    let previous_device = previous.device.as_ref().map(|device| &device.id);
+    let current_device = current.device.as_ref().map(|device| &device.id);
+    if previous_device != current_device {
+        events.push("device_changed");
+    }
+
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::modules::spotify::entity::{Album, DeviceInfo, ExternalUrls, PlaybackInfo, TrackInfo};
+
+    fn song(track: &str, is_playing: bool, progress_ms: i64, device: Option<&str>) -> RealTimeSongInfo {
+        RealTimeSongInfo {
+            track: TrackInfo {
+                id: track.to_string(),
+                name: track.to_string(),
+                artists: Vec::new(),
+                album: Album::default(),
+                duration_ms: 200_000,
+                explicit: false,
+                popularity: 0,
+                preview_url: None,
+                external_urls: ExternalUrls::default(),
+                images: Vec::new(),
+            },
+            playback: PlaybackInfo {
+                is_playing,
+                progress_ms,
+                timestamp: 0,
+                currently_playing_type: "track".to_string(),
+                repeat_state: "off".to_string(),
+                shuffle_state: false,
+                volume_percent: None,
+            },
+            device: device.map(|id| DeviceInfo {
+                id: id.to_string(),
+                is_active: true,
+                is_private_session: false,
+                is_restricted: false,
+                name: id.to_string(),
+                device_type: "Computer".to_string(),
+                volume_percent: 50,
+            }),
+            context: None,
+        }
+    }
+
+    // A previous poll taken `ago` before now
+    fn polled(info: RealTimeSongInfo, ago: Duration) -> (RealTimeSongInfo, Instant) {
+        (info, Instant::now() - ago)
+    }
+
+    #[test]
+    fn first_poll_is_a_track_change() {
+        assert_eq!(detect_events(None, &song("a", true, 0, None)), vec!["track_changed"]);
+    }
+
+    #[test]
+    fn steady_playback_has_no_events() {
+        let previous = polled(song("a", true, 10_000, Some("mac")), Duration::from_secs(1));
+        assert!(detect_events(Some(&previous), &song("a", true, 11_000, Some("mac"))).is_empty());
+    }
+
+    #[test]
+    fn detects_track_change() {
+        let previous = polled(song("a", true, 10_000, Some("mac")), Duration::from_secs(1));
+        assert_eq!(detect_events(Some(&previous), &song("b", true, 0, Some("mac"))), vec!["track_changed"]);
+    }
+
+    #[test]
+    fn detects_pause_and_resume() {
+        let previous = polled(song("a", true, 10_000, None), Duration::from_secs(1));
+        assert_eq!(detect_events(Some(&previous), &song("a", false, 11_000, None)), vec!["paused"]);
+
+        let previous = polled(song("a", false, 10_000, None), Duration::from_secs(30));
+        assert_eq!(detect_events(Some(&previous), &song("a", true, 10_500, None)), vec!["resumed"]);
+    }
+
+    #[test]
+    fn detects_seek() {
+        let previous = polled(song("a", true, 10_000, None), Duration::from_secs(1));
+        assert_eq!(detect_events(Some(&previous), &song("a", true, 90_000, None)), vec!["seeked"]);
+
+        // Paused progress shouldn't move at all
+        let previous = polled(song("a", false, 10_000, None), Duration::from_secs(30));
+        assert_eq!(detect_events(Some(&previous), &song("a", false, 60_000, None)), vec!["seeked"]);
+    }
+
+    #[test]
+    fn detects_device_change() {
+        let previous = polled(song("a", true, 10_000, Some("mac")), Duration::from_secs(1));
+        assert_eq!(detect_events(Some(&previous), &song("a", true, 11_000, Some("phone"))), vec!["device_changed"]);
+        assert_eq!(detect_events(Some(&previous), &song("b", true, 0, None)), vec!["track_changed", "device_changed"]);
+    }
+}
@@ -0,0 +1,180 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+// One row of a breakdown (a language, editor, project, ...)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WakaItem {
+    pub name: String,
+    pub total_seconds: f64,
+    pub percent: f64,
+    pub text: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct WakaGrandTotal {
+    pub total_seconds: f64,
+    pub text: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct WakaRange {
+    pub date: Option<String>,
+    pub start: Option<String>,
+    pub end: Option<String>,
+    pub timezone: Option<String>,
+}
+
+// A single day as returned by `status_bar/today` and `summaries`
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct WakaDay {
+    pub grand_total: WakaGrandTotal,
+    pub languages: Vec<WakaItem>,
+    pub editors: Vec<WakaItem>,
+    pub projects: Vec<WakaItem>,
+    pub range: WakaRange,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StatusBarResponse {
+    pub data: WakaDay,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SummariesResponse {
+    pub data: Vec<WakaDay>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WakaStats {
+    pub range: String,
+    pub status: Option<String>,
+    pub is_up_to_date: bool,
+    pub total_seconds: f64,
+    pub daily_average: f64,
+    pub human_readable_total: Option<String>,
+    pub languages: Vec<WakaItem>,
+    pub editors: Vec<WakaItem>,
+    pub projects: Vec<WakaItem>,
+    pub operating_systems: Vec<WakaItem>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StatsResponse {
+    pub data: WakaStats,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WakaToday {
+    pub date: Option<String>,
+    pub total_seconds: f64,
+    pub text: String,
+    pub languages: Vec<WakaItem>,
+    pub editors: Vec<WakaItem>,
+    pub projects: Vec<WakaItem>,
+}
+
+impl From<WakaDay> for WakaToday {
+    fn from(day: WakaDay) -> Self {
+        Self {
+            date: day.range.date,
+            total_seconds: day.grand_total.total_seconds,
+            text: day.grand_total.text,
+            languages: day.languages,
+            editors: day.editors,
+            projects: day.projects,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WakaDailyTotal {
+    pub date: String,
+    pub total_seconds: f64,
+    pub text: String,
+}
+
+// Several days rolled up, with breakdowns summed across them
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WakaSummary {
+    pub start: Option<String>,
+    pub end: Option<String>,
+    pub total_seconds: f64,
+    pub daily_average_seconds: f64,
+    pub days: Vec<WakaDailyTotal>,
+    pub languages: Vec<WakaItem>,
+    pub editors: Vec<WakaItem>,
+    pub projects: Vec<WakaItem>,
+}
+
+impl WakaSummary {
+    pub fn new(days: &[WakaDay]) -> Self {
+        let total_seconds: f64 = days.iter().map(|day| day.grand_total.total_seconds).sum();
+        Self {
+            start: days.first().and_then(|day| day.range.date.clone()),
+            end: days.last().and_then(|day| day.range.date.clone()),
+            total_seconds,
+            daily_average_seconds: if days.is_empty() {
+                0.0
+            } else {
+                total_seconds / days.len() as f64
+            },
+            days: days
+                .iter()
+                .map(|day| WakaDailyTotal {
+                    date: day.range.date.clone().unwrap_or_default(),
+                    total_seconds: day.grand_total.total_seconds,
+                    text: day.grand_total.text.clone(),
+                })
+                .collect(),
+            languages: combine(days.iter().map(|day| &day.languages)),
+            editors: combine(days.iter().map(|day| &day.editors)),
+            projects: combine(days.iter().map(|day| &day.projects)),
+        }
+    }
+}
+
+// Sum items by name and recompute their share, largest first
+fn combine<'a>(lists: impl Iterator<Item = &'a Vec<WakaItem>>) -> Vec<WakaItem> {
+    let mut seconds: HashMap<&str, f64> = HashMap::new();
+    for item in lists.flatten() {
+        *seconds.entry(item.name.as_str()).or_default() += item.total_seconds;
+    }
+    let total: f64 = seconds.values().sum();
+    let mut items: Vec<WakaItem> = seconds
+        .into_iter()
+        .map(|(name, total_seconds)| WakaItem {
+            name: name.to_string(),
+            total_seconds,
+            percent: if total > 0.0 {
+                (total_seconds / total * 10000.0).round() / 100.0
+            } else {
+                0.0
+            },
+            text: duration_text(total_seconds),
+        })
+        .collect();
+    items.sort_by(|a, b| b.total_seconds.total_cmp(&a.total_seconds));
+    items
+}
+
+// Same shape as WakaTime's own `text` fields, e.g. `3 hrs 4 mins`
+pub fn duration_text(seconds: f64) -> String {
+    let minutes = (seconds / 60.0).floor() as i64;
+    let (hours, minutes) = (minutes / 60, minutes % 60);
+    match (hours, minutes) {
+        (0, minutes) => format!("{} min{}", minutes, if minutes == 1 { "" } else { "s" }),
+        (hours, minutes) => format!(
+            "{} hr{} {} min{}",
+            hours,
+            if hours == 1 { "" } else { "s" },
+            minutes,
+            if minutes == 1 { "" } else { "s" }
+        ),
+    }
+}
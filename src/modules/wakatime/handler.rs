@@ -0,0 +1,118 @@
+use actix_web::{get, web, HttpResponse};
+use serde::Deserialize;
+use serde_json::json;
+use tokio::sync::Mutex;
+
+use crate::{
+    client::NullClient,
+    error::AppError,
+    modules::wakatime::{
+        entity::{WakaItem, WakaStats},
+        manager::STATS_RANGES,
+        WakaManager,
+    },
+};
+
+const MAX_SUMMARY_DAYS: i64 = 31;
+
+#[derive(Debug, Deserialize)]
+pub struct SummaryQuery {
+    #[serde(default = "default_summary_days")]
+    pub days: i64,
+}
+
+fn default_summary_days() -> i64 {
+    7
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StatsQuery {
+    pub range: Option<String>,
+}
+
+#[get("/v1/waka/today")]
+async fn get_waka_today(state: web::Data<Mutex<NullClient>>) -> Result<HttpResponse, AppError> {
+    let data = state.lock().await;
+    let mut redis = data.redis.clone();
+
+    drop(data); // Release the lock early
+
+    let today = WakaManager::get_today(&mut redis).await?;
+    Ok(HttpResponse::Ok().json(json!({"success": true, "data": today})))
+}
+
+#[get("/v1/waka/summary")]
+async fn get_waka_summary(
+    query: web::Query<SummaryQuery>,
+    state: web::Data<Mutex<NullClient>>,
+) -> Result<HttpResponse, AppError> {
+    let data = state.lock().await;
+    let mut redis = data.redis.clone();
+
+    drop(data); // Release the lock early
+
+    if !(1..=MAX_SUMMARY_DAYS).contains(&query.days) {
+        return Err(AppError::BadRequest(format!(
+            "days must be between 1 and {}",
+            MAX_SUMMARY_DAYS
+        )));
+    }
+
+    let summary = WakaManager::get_summary(&mut redis, query.days).await?;
+    Ok(HttpResponse::Ok().json(json!({"success": true, "data": summary})))
+}
+
+#[get("/v1/waka/languages")]
+async fn get_waka_languages(
+    query: web::Query<StatsQuery>,
+    state: web::Data<Mutex<NullClient>>,
+) -> Result<HttpResponse, AppError> {
+    breakdown(&query, &state, |stats| stats.languages).await
+}
+
+#[get("/v1/waka/editors")]
+async fn get_waka_editors(
+    query: web::Query<StatsQuery>,
+    state: web::Data<Mutex<NullClient>>,
+) -> Result<HttpResponse, AppError> {
+    breakdown(&query, &state, |stats| stats.editors).await
+}
+
+#[get("/v1/waka/projects")]
+async fn get_waka_projects(
+    query: web::Query<StatsQuery>,
+    state: web::Data<Mutex<NullClient>>,
+) -> Result<HttpResponse, AppError> {
+    breakdown(&query, &state, |stats| stats.projects).await
+}
+
+// One list out of the precomputed stats for a range
+async fn breakdown(
+    query: &StatsQuery,
+    state: &web::Data<Mutex<NullClient>>,
+    pick: impl FnOnce(WakaStats) -> Vec<WakaItem>,
+) -> Result<HttpResponse, AppError> {
+    let data = state.lock().await;
+    let mut redis = data.redis.clone();
+
+    drop(data); // Release the lock early
+
+    let range = query.range.as_deref().unwrap_or("last_7_days");
+    if !STATS_RANGES.contains(&range) {
+        return Err(AppError::BadRequest(format!(
+            "Unknown range '{}', expected one of {}",
+            range,
+            STATS_RANGES.join(", ")
+        )));
+    }
+
+    let stats = WakaManager::get_stats(&mut redis, range).await?;
+    // WakaTime computes stats in the background; `is_up_to_date` is false until it's done
+    Ok(HttpResponse::Ok().json(json!({
+        "success": true,
+        "range": range,
+        "is_up_to_date": stats.is_up_to_date,
+        "total_seconds": stats.total_seconds,
+        "data": pick(stats)
+    })))
+}
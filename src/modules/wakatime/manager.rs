@@ -0,0 +1,138 @@
+use std::future::Future;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::{Duration, Utc};
+use envconfig::Envconfig;
+use log::info;
+use redis::{aio::ConnectionManager, AsyncCommands};
+use reqwest::StatusCode;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{config::Config, db::redis::RedisManager, error::AppError};
+
+use super::entity::{
+    StatsResponse, StatusBarResponse, SummariesResponse, WakaDay, WakaStats, WakaSummary,
+    WakaToday,
+};
+
+const API_BASE: &str = "https://wakatime.com/api/v1/users/current";
+
+// Ranges WakaTime precomputes stats for
+pub const STATS_RANGES: [&str; 5] = [
+    "last_7_days",
+    "last_30_days",
+    "last_6_months",
+    "last_year",
+    "all_time",
+];
+
+#[derive(Clone)]
+pub struct WakaManager;
+
+impl WakaManager {
+    pub async fn get_today(redis: &mut RedisManager) -> Result<WakaToday, AppError> {
+        Self::cached(redis, "waka:today", || async {
+            let response: StatusBarResponse = Self::request("/status_bar/today", &[]).await?;
+            Ok(WakaToday::from(response.data))
+        })
+        .await
+    }
+
+    // Rolled-up summary of the last `days` days, today included
+    pub async fn get_summary(redis: &mut RedisManager, days: i64) -> Result<WakaSummary, AppError> {
+        Self::cached(redis, &format!("waka:summary:{}", days), || async {
+            Ok(WakaSummary::new(&Self::fetch_summaries(days).await?))
+        })
+        .await
+    }
+
+    pub async fn get_stats(redis: &mut RedisManager, range: &str) -> Result<WakaStats, AppError> {
+        Self::cached(redis, &format!("waka:stats:{}", range), || async {
+            let response: StatsResponse = Self::request(&format!("/stats/{}", range), &[]).await?;
+            Ok(response.data)
+        })
+        .await
+    }
+
+    // Per-day summaries straight from WakaTime, oldest first
+    pub async fn fetch_summaries(days: i64) -> Result<Vec<WakaDay>, AppError> {
+        // Count days in the same zone WakaTime buckets them by, or today may be missed
+        let config = Config::init_from_env()?;
+        let tz: chrono_tz::Tz = config.waka_timezone.parse().map_err(|_| {
+            AppError::WakaError(format!("Unknown WAKA_TIMEZONE '{}'", config.waka_timezone))
+        })?;
+        let end = Utc::now().with_timezone(&tz).date_naive();
+        let start = end - Duration::days(days - 1);
+        let response: SummariesResponse = Self::request(
+            "/summaries",
+            &[
+                ("start", start.to_string()),
+                ("end", end.to_string()),
+                ("timezone", config.waka_timezone),
+            ],
+        )
+        .await?;
+        Ok(response.data)
+    }
+
+    async fn request<T: DeserializeOwned>(
+        path: &str,
+        params: &[(&str, String)],
+    ) -> Result<T, AppError> {
+        let config = Config::init_from_env()?;
+        let response = reqwest::Client::new()
+            .get(format!("{}{}", API_BASE, path))
+            .query(params)
+            .header(
+                "Authorization",
+                format!("Basic {}", STANDARD.encode(&config.waka_api)),
+            )
+            .send()
+            .await
+            .map_err(|e| AppError::WakaError(format!("WakaTime unreachable: {}", e)))?;
+
+        match response.status() {
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => Err(AppError::WakaError(
+                "WakaTime rejected the API key, check WAKA_API".to_string(),
+            )),
+            status if !status.is_success() => Err(AppError::WakaError(format!(
+                "WakaTime returned {} for {}",
+                status, path
+            ))),
+            _ => {
+                let body = response
+                    .text()
+                    .await
+                    .map_err(|e| AppError::WakaError(format!("WakaTime unreachable: {}", e)))?;
+                serde_json::from_str(&body).map_err(|e| {
+                    AppError::WakaError(format!("unexpected response for {}: {}", path, e))
+                })
+            }
+        }
+    }
+
+    // Serve from Redis for WAKA_TTL seconds, otherwise fetch and store
+    async fn cached<T, F, Fut>(redis: &mut RedisManager, key: &str, fetch: F) -> Result<T, AppError>
+    where
+        T: Serialize + DeserializeOwned,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, AppError>>,
+    {
+        let cached: Option<String> = redis.connection.get(key).await?;
+        if let Some(value) = cached.and_then(|data| serde_json::from_str(&data).ok()) {
+            return Ok(value);
+        }
+
+        info!("Fetching fresh WakaTime data for {}", key);
+        let config = Config::init_from_env()?;
+        let value = fetch().await?;
+        redis::cmd("SET")
+            .arg(key)
+            .arg(serde_json::to_string(&value)?)
+            .arg("EX")
+            .arg(config.waka_ttl.max(1))
+            .query_async::<ConnectionManager, String>(&mut redis.connection)
+            .await?;
+        Ok(value)
+    }
+}
@@ -0,0 +1,9 @@
+pub mod entity;
+pub mod handler;
+pub mod manager;
+pub mod provider;
+pub mod repository;
+
+pub use manager::WakaManager;
+pub use provider::WakaProvider;
+pub use repository::WakaRepository;
@@ -0,0 +1,78 @@
+use std::time::Duration;
+
+use actix_web::web;
+use async_trait::async_trait;
+use envconfig::Envconfig;
+use log::info;
+use serde_json::{json, Value};
+use tokio::sync::Mutex;
+
+use crate::{client::NullClient, config::Config, error::AppError, provider::Provider};
+
+use super::{
+    handler::{
+        get_waka_editors, get_waka_languages, get_waka_projects, get_waka_summary, get_waka_today,
+    },
+    WakaManager, WakaRepository,
+};
+
+// Days re-fetched on every snapshot, so late heartbeats still land on the right day
+const SNAPSHOT_DAYS: i64 = 7;
+
+pub struct WakaProvider;
+
+#[async_trait]
+impl Provider for WakaProvider {
+    fn name(&self) -> &'static str {
+        "wakatime"
+    }
+
+    fn missing_config(&self, config: &Config) -> Vec<&'static str> {
+        if config.waka_api.is_empty() {
+            vec!["WAKA_API"]
+        } else {
+            Vec::new()
+        }
+    }
+
+    fn routes(&self, cfg: &mut web::ServiceConfig) {
+        cfg.service(get_waka_today);
+        cfg.service(get_waka_summary);
+        cfg.service(get_waka_languages);
+        cfg.service(get_waka_editors);
+        cfg.service(get_waka_projects);
+    }
+
+    fn refresh_interval(&self) -> Option<Duration> {
+        let config = Config::init_from_env().ok()?;
+        match config.waka_snapshot_interval {
+            0 => None,
+            secs => Some(Duration::from_secs(secs.max(60))),
+        }
+    }
+
+    // Snapshot the last week of daily totals into Postgres
+    async fn refresh(&self, state: &web::Data<Mutex<NullClient>>) -> Result<(), AppError> {
+        let data = state.lock().await;
+        let pool = data.postgres.pool.clone();
+        drop(data);
+
+        let days = WakaManager::fetch_summaries(SNAPSHOT_DAYS).await?;
+        let recorded = WakaRepository::record_days(&pool, &days).await?;
+        info!("Recorded {} WakaTime daily summaries", recorded);
+        Ok(())
+    }
+
+    // Coding time so far today
+    async fn now(&self, state: &web::Data<Mutex<NullClient>>) -> Result<Option<Value>, AppError> {
+        let mut redis = state.lock().await.redis.clone();
+        let today = WakaManager::get_today(&mut redis).await?;
+        Ok(Some(json!({
+            "date": today.date,
+            "total_seconds": today.total_seconds,
+            "text": today.text,
+            "top_language": today.languages.first().map(|language| &language.name),
+            "top_project": today.projects.first().map(|project| &project.name)
+        })))
+    }
+}
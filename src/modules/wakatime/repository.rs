@@ -0,0 +1,49 @@
+use chrono::NaiveDate;
+use sqlx::{types::Json, Pool, Postgres};
+
+use crate::error::AppError;
+
+use super::entity::WakaDay;
+
+#[derive(Clone)]
+pub struct WakaRepository;
+
+impl WakaRepository {
+    // Upsert one row per day; recent days keep changing as heartbeats arrive
+    pub async fn record_days(pool: &Pool<Postgres>, days: &[WakaDay]) -> Result<u64, AppError> {
+        let mut tx = pool.begin().await?;
+        let mut recorded = 0;
+
+        for day in days {
+            let Some(date) = day
+                .range
+                .date
+                .as_deref()
+                .and_then(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok())
+            else {
+                continue;
+            };
+            recorded += sqlx::query(
+                "INSERT INTO waka_daily_summaries (date, total_seconds, languages, editors, projects)
+                 VALUES ($1, $2, $3, $4, $5)
+                 ON CONFLICT (date) DO UPDATE SET
+                    total_seconds = EXCLUDED.total_seconds,
+                    languages = EXCLUDED.languages,
+                    editors = EXCLUDED.editors,
+                    projects = EXCLUDED.projects,
+                    fetched_at = NOW()",
+            )
+            .bind(date)
+            .bind(day.grand_total.total_seconds)
+            .bind(Json(&day.languages))
+            .bind(Json(&day.editors))
+            .bind(Json(&day.projects))
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        }
+
+        tx.commit().await?;
+        Ok(recorded)
+    }
+}
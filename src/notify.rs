@@ -0,0 +1,158 @@
+use envconfig::Envconfig;
+use lettre::{
+    message::{header::ContentType, Mailbox},
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+};
+use log::{error, warn};
+use serde::Serialize;
+use serde_json::json;
+use sqlx::{Pool, Postgres};
+
+use crate::{config::Config, error::AppError};
+
+// An alert raised by a background job
+#[derive(Debug, Clone, Serialize)]
+pub struct Notification {
+    // Machine-readable type, e.g. `runner_offline`
+    pub kind: String,
+    pub title: String,
+    pub message: String,
+}
+
+// Delivers notifications to every configured sink
+#[derive(Clone)]
+pub struct Notifier;
+
+impl Notifier {
+    // Returns how many sinks accepted the notification
+    pub async fn send(notification: &Notification) -> Result<usize, AppError> {
+        let config = Config::init_from_env()?;
+        let client = reqwest::Client::new();
+        let mut delivered = 0;
+        let mut attempted = 0;
+
+        if !config.notify_webhook_url.is_empty() {
+            attempted += 1;
+            let result = client
+                .post(&config.notify_webhook_url)
+                .json(notification)
+                .send()
+                .await
+                .and_then(|res| res.error_for_status());
+            match result {
+                Ok(_) => delivered += 1,
+                Err(e) => error!("Webhook notification failed: {}", e),
+            }
+        }
+
+        if !config.notify_discord_webhook_url.is_empty() {
+            attempted += 1;
+            let content = format!("**{}**\n{}", notification.title, notification.message);
+            let result = client
+                .post(&config.notify_discord_webhook_url)
+                .json(&json!({ "content": content }))
+                .send()
+                .await
+                .and_then(|res| res.error_for_status());
+            match result {
+                Ok(_) => delivered += 1,
+                Err(e) => error!("Discord notification failed: {}", e),
+            }
+        }
+
+        if !config.notify_smtp_url.is_empty() {
+            attempted += 1;
+            match Self::send_email(&config, notification).await {
+                Ok(()) => delivered += 1,
+                Err(e) => error!("Email notification failed: {}", e),
+            }
+        }
+
+        if attempted == 0 {
+            warn!("No notification sink configured, dropping: {}", notification.title);
+        }
+        Ok(delivered)
+    }
+
+    // Send unless this kind of notification already went out for the target in `period`
+    // (e.g. a date); returns whether it was sent now
+    pub async fn send_once(
+        pool: &Pool<Postgres>,
+        notification: &Notification,
+        target: &str,
+        period: &str,
+    ) -> Result<bool, AppError> {
+        if Self::already_sent(pool, &notification.kind, target, period).await? {
+            return Ok(false);
+        }
+
+        // Nothing is recorded when every sink failed, so the next run retries
+        let delivered = Self::send(notification).await?;
+        if delivered == 0 {
+            return Ok(false);
+        }
+
+        sqlx::query(
+            "INSERT INTO sent_notifications (kind, target, period, title, message, sinks)
+             VALUES ($1, $2, $3, $4, $5, $6)
+             ON CONFLICT (kind, target, period) DO NOTHING",
+        )
+        .bind(&notification.kind)
+        .bind(target)
+        .bind(period)
+        .bind(&notification.title)
+        .bind(&notification.message)
+        .bind(delivered as i32)
+        .execute(pool)
+        .await?;
+        Ok(true)
+    }
+
+    pub async fn already_sent(
+        pool: &Pool<Postgres>,
+        kind: &str,
+        target: &str,
+        period: &str,
+    ) -> Result<bool, AppError> {
+        let exists: bool = sqlx::query_scalar(
+            "SELECT EXISTS (
+                SELECT 1 FROM sent_notifications WHERE kind = $1 AND target = $2 AND period = $3
+             )",
+        )
+        .bind(kind)
+        .bind(target)
+        .bind(period)
+        .fetch_one(pool)
+        .await?;
+        Ok(exists)
+    }
+
+    async fn send_email(config: &Config, notification: &Notification) -> Result<(), String> {
+        let from: Mailbox = config
+            .notify_email_from
+            .parse()
+            .map_err(|e| format!("invalid NOTIFY_EMAIL_FROM: {}", e))?;
+        let mut builder = Message::builder().from(from).subject(&notification.title);
+        let mut recipients = 0;
+        for to in config.notify_email_to.split(',').map(str::trim).filter(|to| !to.is_empty()) {
+            let mailbox: Mailbox = to
+                .parse()
+                .map_err(|e| format!("invalid NOTIFY_EMAIL_TO entry {}: {}", to, e))?;
+            builder = builder.to(mailbox);
+            recipients += 1;
+        }
+        if recipients == 0 {
+            return Err("NOTIFY_EMAIL_TO is empty".to_string());
+        }
+
+        let email = builder
+            .header(ContentType::TEXT_PLAIN)
+            .body(notification.message.clone())
+            .map_err(|e| e.to_string())?;
+        let mailer = AsyncSmtpTransport::<Tokio1Executor>::from_url(&config.notify_smtp_url)
+            .map_err(|e| e.to_string())?
+            .build();
+        mailer.send(email).await.map_err(|e| e.to_string())?;
+        Ok(())
+    }
+}
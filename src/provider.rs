@@ -0,0 +1,126 @@
+use std::{sync::Arc, time::Duration};
+
+use actix_web::web;
+use async_trait::async_trait;
+use log::{error, info, warn};
+use serde_json::Value;
+use tokio::sync::Mutex;
+
+use crate::{client::NullClient, config::Config, error::AppError, modules};
+
+// Outcome of a provider health check that didn't fail
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProviderHealth {
+    Up,
+    // Loaded but waiting on optional setup, e.g. no linked account yet
+    NotConfigured(String),
+}
+
+// Common shape shared by every data source in `src/modules/`
+#[async_trait]
+pub trait Provider: Send + Sync {
+    // Unique name, used in logs, `/health` and `DISABLED_PROVIDERS`
+    fn name(&self) -> &'static str;
+
+    // Environment variables this provider needs but that are not set
+    fn missing_config(&self, _config: &Config) -> Vec<&'static str> {
+        Vec::new()
+    }
+
+    // Mount the provider's endpoints
+    fn routes(&self, cfg: &mut web::ServiceConfig);
+
+    // Cheap check reported by `/health`; an error means the provider is down
+    async fn health(&self, _state: &web::Data<Mutex<NullClient>>) -> Result<ProviderHealth, AppError> {
+        Ok(ProviderHealth::Up)
+    }
+
+    // How often `refresh` should run in the background, if at all
+    fn refresh_interval(&self) -> Option<Duration> {
+        None
+    }
+
+    // Background refresh hook
+    async fn refresh(&self, _state: &web::Data<Mutex<NullClient>>) -> Result<(), AppError> {
+        Ok(())
+    }
+
+    // Live status for `/v1/now`; `None` leaves the provider out
+    async fn now(&self, _state: &web::Data<Mutex<NullClient>>) -> Result<Option<Value>, AppError> {
+        Ok(None)
+    }
+}
+
+// Holds every provider that passed its configuration checks
+pub struct ProviderRegistry {
+    providers: Vec<Arc<dyn Provider>>,
+}
+
+impl ProviderRegistry {
+    pub fn load(config: &Config) -> Self {
+        let disabled: Vec<&str> = config
+            .disabled_providers
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .collect();
+
+        let providers = modules::providers()
+            .into_iter()
+            .filter(|provider| {
+                if disabled.contains(&provider.name()) {
+                    info!("Provider {} disabled by configuration", provider.name());
+                    return false;
+                }
+                let missing = provider.missing_config(config);
+                if !missing.is_empty() {
+                    warn!(
+                        "Provider {} not loaded, missing: {}",
+                        provider.name(),
+                        missing.join(", ")
+                    );
+                    return false;
+                }
+                info!("Loaded provider {}", provider.name());
+                true
+            })
+            .collect();
+
+        Self { providers }
+    }
+
+    pub fn providers(&self) -> &[Arc<dyn Provider>] {
+        &self.providers
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        self.providers.iter().map(|provider| provider.name()).collect()
+    }
+
+    // Mount the routes of every loaded provider
+    pub fn configure(&self, cfg: &mut web::ServiceConfig) {
+        for provider in &self.providers {
+            provider.routes(cfg);
+        }
+    }
+
+    // Spawn a background task for every provider with a refresh interval
+    pub fn spawn_refreshers(&self, state: web::Data<Mutex<NullClient>>) {
+        for provider in &self.providers {
+            let Some(interval) = provider.refresh_interval() else {
+                continue;
+            };
+            let provider = Arc::clone(provider);
+            let state = web::Data::clone(&state);
+            tokio::spawn(async move {
+                let mut ticker = tokio::time::interval(interval);
+                loop {
+                    ticker.tick().await;
+                    if let Err(e) = provider.refresh(&state).await {
+                        error!("Refresh of provider {} failed: {}", provider.name(), e);
+                    }
+                }
+            });
+        }
+    }
+}